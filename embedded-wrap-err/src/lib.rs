@@ -1,6 +1,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use core::fmt::Write;
+#[cfg(not(feature = "std"))]
 use defmt::error;
 use heapless::String;
 pub type Result<T> = core::result::Result<T, &'static str>;
@@ -20,9 +21,9 @@ impl<T, E: core::fmt::Debug> core::result::Result<T, E> {
 #[extension_traits::extension(pub trait IntoWrapErrExt)]
 impl<T, E: defmt::Format> core::result::Result<T, E> {
     fn into_wrap_err(self, context: &'static str) -> Result<T> {
-        self.map_err(|e| {
+        self.map_err(|_e| {
             #[cfg(not(feature = "std"))]
-            error!("ERROR: {:?}", e);
+            error!("ERROR: {:?}", _e);
             context
         })
     }
@@ -31,9 +32,9 @@ impl<T, E: defmt::Format> core::result::Result<T, E> {
 #[extension_traits::extension(pub trait WrapErrorExt)]
 impl<T> Result<T> {
     fn wrap_err(self, context: &'static str) -> Result<T> {
-        self.map_err(|error| {
+        self.map_err(|_error| {
             #[cfg(not(feature = "std"))]
-            error!("CAUSED BY: {}", error);
+            error!("CAUSED BY: {}", _error);
             context
         })
     }
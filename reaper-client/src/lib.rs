@@ -0,0 +1,186 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use embedded_io_async::Read;
+use embedded_nal_async::{Dns, TcpConnect};
+use embedded_wrap_err::{IntoWrapErrDebugExt, Result, WrapErrorExt};
+use reaper::{FolderDepths, MarkerList, ReaperCommand, ReaperQuery, ReaperStatus, Record, ResponseParser, StatusBuilder, MAX_QUERY_COMMANDS};
+use reqwless::{
+    client::{HttpClient, HttpResource},
+    request::{Method, RequestBuilder},
+};
+
+#[cfg(feature = "std")]
+pub mod recorder;
+#[cfg(feature = "std")]
+pub mod std_adapter;
+
+/// Web interface client, every buffer it needs is sized by its const parameters:
+///
+/// - `MAX_TRACK_COUNT`: tracks kept per [`ReaperStatus`], the rest of the response is dropped
+/// - `MAX_LINE_SIZE`: longest response line, see [`ResponseParser`]
+/// - `RX_BUFFER_SIZE`: response headers plus the first chunk of the body
+/// - `BODY_CHUNK_SIZE`: how much of the body is read at once
+/// - `MAX_PATH_SIZE`: longest request path, see [`ReaperQuery::to_path`]
+pub struct ReaperClient<'stack, T, const MAX_TRACK_COUNT: usize, const MAX_LINE_SIZE: usize, const RX_BUFFER_SIZE: usize, const BODY_CHUNK_SIZE: usize, const MAX_PATH_SIZE: usize>
+where
+    T: TcpConnect + 'stack,
+{
+    http_resource: HttpResource<'stack, T::Connection<'stack>>,
+    /// `I_FOLDERDEPTH` by track number, it takes a command per track so it's only refreshed once in a while
+    folder_depths: FolderDepths<MAX_TRACK_COUNT>,
+}
+
+/// one command of every folder depth query goes to the `TRACK` range it belongs to
+const FOLDER_DEPTH_CHUNK_SIZE: usize = MAX_QUERY_COMMANDS - 1;
+
+impl<'stack, 'client: 'stack, T, const MAX_TRACK_COUNT: usize, const MAX_LINE_SIZE: usize, const RX_BUFFER_SIZE: usize, const BODY_CHUNK_SIZE: usize, const MAX_PATH_SIZE: usize>
+    ReaperClient<'stack, T, MAX_TRACK_COUNT, MAX_LINE_SIZE, RX_BUFFER_SIZE, BODY_CHUNK_SIZE, MAX_PATH_SIZE>
+where
+    T: TcpConnect + 'stack,
+{
+    pub async fn new<D>(client: &'client mut HttpClient<'stack, T, D>, base_url: &'stack str) -> Result<Self>
+    where
+        D: Dns,
+    {
+        client
+            .resource(base_url)
+            .await
+            .into_wrap_err_dbg("creating resource")
+            .map(|http_resource| Self {
+                http_resource,
+                folder_depths: FolderDepths::new(),
+            })
+    }
+
+    /// what [`ReaperClient::get_status`] asks for
+    pub fn status_query() -> ReaperQuery<'static> {
+        ReaperQuery::new()
+            .transport()
+            .beat_position()
+            .track_range(0, MAX_TRACK_COUNT as u16 - 1)
+    }
+
+    pub async fn get_status(&mut self) -> Result<ReaperStatus<MAX_TRACK_COUNT>> {
+        self.get_status_with(|_| {}).await
+    }
+
+    /// same as `get_status`, but also hands every chunk of the raw response body to `on_body`
+    pub async fn get_status_with(&mut self, on_body: impl FnMut(&[u8])) -> Result<ReaperStatus<MAX_TRACK_COUNT>> {
+        let mut status = self.query_with(&Self::status_query(), on_body).await?;
+        self.folder_depths.apply(&mut status);
+        Ok(status)
+    }
+
+    /// fetches the folder depth of every track that fits into `MAX_TRACK_COUNT`, a chunk of tracks at a time
+    pub async fn refresh_folder_depths(&mut self) -> Result<()> {
+        for (first, last) in Self::folder_depth_chunks() {
+            let status = self
+                .query(&Self::folder_depth_query(first, last))
+                .await
+                .wrap_err("fetching folder depths")?;
+            if !self.folder_depths.update(first, last, &status) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// the inclusive track ranges [`ReaperClient::refresh_folder_depths`] asks for, one query each
+    fn folder_depth_chunks() -> impl Iterator<Item = (u16, u16)> {
+        (0..MAX_TRACK_COUNT)
+            .step_by(FOLDER_DEPTH_CHUNK_SIZE)
+            .map(|first| (first as u16, ((first + FOLDER_DEPTH_CHUNK_SIZE).min(MAX_TRACK_COUNT) - 1) as u16))
+    }
+
+    fn folder_depth_query(first: u16, last: u16) -> ReaperQuery<'static> {
+        ReaperQuery::new()
+            .track_range(first, last)
+            .folder_depths(first, last)
+    }
+
+    pub async fn query(&mut self, query: &ReaperQuery<'_>) -> Result<ReaperStatus<MAX_TRACK_COUNT>> {
+        self.query_with(query, |_| {}).await
+    }
+
+    /// same as `query`, but also hands every chunk of the raw response body to `on_body`
+    pub async fn query_with(&mut self, query: &ReaperQuery<'_>, on_body: impl FnMut(&[u8])) -> Result<ReaperStatus<MAX_TRACK_COUNT>> {
+        let mut builder = StatusBuilder::<MAX_TRACK_COUNT>::new();
+        self.fetch(query, on_body, |record| {
+            builder.push(record);
+            Ok(())
+        })
+        .await?;
+        builder.finish(query)
+    }
+
+    /// fetches markers and regions, they change too rarely to be part of every status poll
+    pub async fn get_markers(&mut self) -> Result<MarkerList> {
+        let mut markers = MarkerList::new();
+        self.fetch(&ReaperQuery::new().marker_list().region_list(), |_| {}, |record| markers.push(record))
+            .await
+            .map(|_| markers)
+    }
+
+    /// sends `query` and hands every record of the response to `on_record` as soon as its line is complete
+    async fn fetch(&mut self, query: &ReaperQuery<'_>, mut on_body: impl FnMut(&[u8]), mut on_record: impl FnMut(Record) -> Result<()>) -> Result<()> {
+        query
+            .check_track_capacity(MAX_TRACK_COUNT)
+            .wrap_err("validating query")?;
+        let url = query.to_path::<MAX_PATH_SIZE>().wrap_err("building url")?;
+        let mut buffer = [0; RX_BUFFER_SIZE];
+        let mut reader = self
+            .http_resource
+            .request(Method::GET, &url)
+            .headers(&[("Connection", "keep-alive")])
+            .send(&mut buffer)
+            .await
+            .into_wrap_err_dbg("sending")?
+            .body()
+            .reader();
+        let mut parser = ResponseParser::<MAX_LINE_SIZE>::new();
+        let mut parsed = Ok(());
+        let mut chunk = [0; BODY_CHUNK_SIZE];
+        loop {
+            match reader
+                .read(&mut chunk)
+                .await
+                .into_wrap_err_dbg("reading")?
+            {
+                0 => break,
+                read => {
+                    on_body(&chunk[..read]);
+                    // a response that fails to parse is still read to its end, so the connection can be reused
+                    if parsed.is_ok() {
+                        parsed = parser.feed(&chunk[..read], |record| record.and_then(&mut on_record));
+                    }
+                }
+            }
+        }
+        parsed
+            .and_then(|_| parser.finish(|record| record.and_then(&mut on_record)))
+            .wrap_err("parsing response")
+    }
+
+    pub async fn send_command(&mut self, command: &ReaperCommand<'_>) -> Result<()> {
+        let url = command.to_path::<MAX_PATH_SIZE>().wrap_err("building url")?;
+        let mut buffer = [0; RX_BUFFER_SIZE];
+        let mut reader = self
+            .http_resource
+            .request(Method::GET, &url)
+            .headers(&[("Connection", "keep-alive")])
+            .send(&mut buffer)
+            .await
+            .into_wrap_err_dbg("sending")?
+            .body()
+            .reader();
+        // the body has to be drained so the connection can be reused
+        let mut chunk = [0; BODY_CHUNK_SIZE];
+        while reader
+            .read(&mut chunk)
+            .await
+            .into_wrap_err_dbg("reading")?
+            > 0
+        {}
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+//! Writes the responses [`ReaperClient`](crate::ReaperClient) receives in the recording format of [`reaper::RecordedResponse`].
+
+use crate::ReaperClient;
+use embedded_nal_async::TcpConnect;
+use embedded_wrap_err::{IntoWrapErrDebugExt, Result, WrapErrorExt};
+use reaper::{ReaperQuery, RecordedResponse, ReaperStatus};
+use std::{
+    io::{self, Write},
+    time::Instant,
+};
+
+/// Collects the body of a response chunk by chunk and appends it to `out` once it is complete, byte for byte.
+pub struct Recorder<W> {
+    out: W,
+    started_at: Instant,
+    body: Vec<u8>,
+}
+
+impl<W: Write> Recorder<W> {
+    /// timestamps count from this call
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            started_at: Instant::now(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.body.extend_from_slice(chunk);
+    }
+
+    /// writes everything pushed since the last call as the response to `path`, requests that never got a body are skipped
+    pub fn finish_response(&mut self, path: &str) -> io::Result<()> {
+        if self.body.is_empty() {
+            return Ok(());
+        }
+        RecordedResponse {
+            at_millis: self.started_at.elapsed().as_millis() as u64,
+            path,
+            body: &self.body,
+        }
+        .write(&mut self.out)
+        .and_then(|_| self.out.flush())?;
+        self.body.clear();
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<'stack, T, const MAX_TRACK_COUNT: usize, const MAX_LINE_SIZE: usize, const RX_BUFFER_SIZE: usize, const BODY_CHUNK_SIZE: usize, const MAX_PATH_SIZE: usize>
+    ReaperClient<'stack, T, MAX_TRACK_COUNT, MAX_LINE_SIZE, RX_BUFFER_SIZE, BODY_CHUNK_SIZE, MAX_PATH_SIZE>
+where
+    T: TcpConnect + 'stack,
+{
+    /// same as `get_status`, but records the raw response, even (and especially) when it fails to parse.
+    /// Replays fill in the folder depths from the responses of [`ReaperClient::refresh_folder_depths_recorded`].
+    pub async fn get_status_recorded<W: Write>(&mut self, recorder: &mut Recorder<W>) -> Result<ReaperStatus<MAX_TRACK_COUNT>> {
+        let path = Self::status_query()
+            .to_path::<MAX_PATH_SIZE>()
+            .wrap_err("building url")?;
+        let status = self
+            .get_status_with(|chunk| recorder.push(chunk))
+            .await;
+        recorder
+            .finish_response(&path)
+            .into_wrap_err_dbg("writing recording")?;
+        status
+    }
+
+    /// same as `refresh_folder_depths`, but records every response, see [`reaper::FolderDepths::replay`]
+    pub async fn refresh_folder_depths_recorded<W: Write>(&mut self, recorder: &mut Recorder<W>) -> Result<()> {
+        for (first, last) in Self::folder_depth_chunks() {
+            let status = self
+                .query_recorded(&Self::folder_depth_query(first, last), recorder)
+                .await
+                .wrap_err("fetching folder depths")?;
+            if !self.folder_depths.update(first, last, &status) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// same as `query`, but records the raw response, even when it fails to parse
+    pub async fn query_recorded<W: Write>(&mut self, query: &ReaperQuery<'_>, recorder: &mut Recorder<W>) -> Result<ReaperStatus<MAX_TRACK_COUNT>> {
+        let path = query
+            .to_path::<MAX_PATH_SIZE>()
+            .wrap_err("building url")?;
+        let status = self
+            .query_with(query, |chunk| recorder.push(chunk))
+            .await;
+        recorder
+            .finish_response(&path)
+            .into_wrap_err_dbg("writing recording")?;
+        status
+    }
+}
@@ -0,0 +1,107 @@
+//! Blocking `std::net` implementations of the network traits [`ReaperClient`](crate::ReaperClient) is generic over.
+//!
+//! Every future completes on its first poll, so any executor (or a plain
+//! poll loop) can drive the client on a host.
+
+use embedded_io_async::{ErrorType, Read, Write};
+use embedded_nal_async::{AddrType, Dns, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpConnect};
+use std::{
+    future::Future,
+    io::{Read as _, Write as _},
+    net::{TcpStream, ToSocketAddrs},
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+/// Runs a future of these adapters to completion on the current thread.
+///
+/// They block instead of returning `Pending`, so there is no waker to wait for and this just polls again.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+/// Connects with [`TcpStream::connect`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdTcp;
+
+/// A connection made by [`StdTcp`].
+#[derive(Debug)]
+pub struct StdConnection(pub TcpStream);
+
+impl ErrorType for StdConnection {
+    type Error = std::io::Error;
+}
+
+impl Read for StdConnection {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for StdConnection {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.0.write(buf)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.0.flush()
+    }
+}
+
+fn invalid_input(message: &'static str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, message)
+}
+
+impl TcpConnect for StdTcp {
+    type Error = std::io::Error;
+    type Connection<'a> = StdConnection;
+
+    async fn connect<'a>(&'a self, remote: SocketAddr) -> Result<Self::Connection<'a>, Self::Error> {
+        // both address types print the same way, so this works whichever one embedded-nal uses
+        let remote = remote
+            .to_string()
+            .parse::<std::net::SocketAddr>()
+            .map_err(|_| invalid_input("unsupported socket address"))?;
+        TcpStream::connect(remote).map(StdConnection)
+    }
+}
+
+/// Resolves with [`ToSocketAddrs`], i.e. the resolver of the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdDns;
+
+impl Dns for StdDns {
+    type Error = std::io::Error;
+
+    async fn get_host_by_name(&self, host: &str, addr_type: AddrType) -> Result<IpAddr, Self::Error> {
+        (host, 0)
+            .to_socket_addrs()?
+            .map(|address| address.ip())
+            .find(|address| match addr_type {
+                AddrType::IPv4 => address.is_ipv4(),
+                AddrType::IPv6 => address.is_ipv6(),
+                AddrType::Either => true,
+            })
+            .map(|address| match address {
+                std::net::IpAddr::V4(address) => {
+                    let [a, b, c, d] = address.octets();
+                    IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+                }
+                std::net::IpAddr::V6(address) => {
+                    let [a, b, c, d, e, f, g, h] = address.segments();
+                    IpAddr::V6(Ipv6Addr::new(a, b, c, d, e, f, g, h))
+                }
+            })
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no address for host"))
+    }
+
+    async fn get_host_by_address(&self, _addr: IpAddr, _result: &mut [u8]) -> Result<usize, Self::Error> {
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "reverse lookups are not supported"))
+    }
+}
@@ -0,0 +1,70 @@
+use reaper::PlayState;
+use reaper_client::{
+    std_adapter::{block_on, StdDns, StdTcp},
+    ReaperClient,
+};
+use reqwless::client::HttpClient;
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread,
+};
+
+type Client<'a> = ReaperClient<'a, StdTcp, 8, 256, 1024, 256, 512>;
+
+const BODY: &str = "\
+TRANSPORT\t1\t12.345000\t0\t0:12.345\t5.2.00
+BEATPOS\t1\t12.345000\t24.690000\t6\t0.690000\t4\t4
+TRACK\t0\tMASTER\t0\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t1\t0
+TRACK\t1\tKick\t8\t1.000000\t0.000000\t-300\t-412\t1.000000\t3\t0\t0\t0\t0
+";
+
+/// answers every request on a single keep-alive connection with `BODY`, returning the requested paths
+fn serve(listener: TcpListener, requests: usize) -> thread::JoinHandle<Vec<String>> {
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("accepting");
+        let mut reader = BufReader::new(stream.try_clone().expect("cloning stream"));
+        let mut stream = stream;
+        (0..requests)
+            .map(|_| {
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).expect("reading request line");
+                let mut header = String::new();
+                while header != "\r\n" {
+                    header.clear();
+                    reader.read_line(&mut header).expect("reading header");
+                }
+                write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{BODY}", BODY.len()).expect("responding");
+                request_line
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_owned()
+            })
+            .collect()
+    })
+}
+
+#[test]
+fn fetches_status_from_a_local_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("binding");
+    let base_url = format!("http://{}", listener.local_addr().expect("bound address"));
+    let server = serve(listener, 2);
+
+    let (tcp, dns) = (StdTcp, StdDns);
+    let mut http_client = HttpClient::new(&tcp, &dns);
+    let (first, second) = block_on(async {
+        let mut client = Client::new(&mut http_client, &base_url)
+            .await
+            .expect("connecting");
+        let first = client.get_status().await.expect("first status");
+        let second = client.get_status().await.expect("second status over the same connection");
+        (first, second)
+    });
+
+    assert_eq!(first, second);
+    assert_eq!(first.transport.play_state, PlayState::Playing);
+    assert_eq!(first.tracks.len(), 2);
+    assert_eq!(first.tracks[1].name.as_str(), "Kick");
+    assert_eq!(server.join().expect("server thread"), ["/_/TRANSPORT;BEATPOS;TRACK/0-7"; 2]);
+}
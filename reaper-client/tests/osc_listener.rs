@@ -0,0 +1,51 @@
+use reaper::{DatagramSource, OscListener, PlayState};
+use reaper_client::std_adapter::block_on;
+use std::{net::UdpSocket, time::Duration};
+
+struct StdUdp(UdpSocket);
+
+impl DatagramSource for StdUdp {
+    async fn receive(&mut self, buffer: &mut [u8]) -> embedded_wrap_err::Result<usize> {
+        self.0.recv(buffer).map_err(|_| "receiving from std socket")
+    }
+}
+
+fn float_message(address: &str, value: f32) -> Vec<u8> {
+    let mut out = Vec::new();
+    [address, ",f"].iter().for_each(|string| {
+        out.extend_from_slice(string.as_bytes());
+        out.push(0);
+        while !out.len().is_multiple_of(4) {
+            out.push(0);
+        }
+    });
+    out.extend_from_slice(&value.to_be_bytes());
+    out
+}
+
+#[test]
+fn listener_applies_datagrams_from_a_local_socket() {
+    let socket = UdpSocket::bind("127.0.0.1:0").expect("binding listener");
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("setting timeout");
+    let address = socket.local_addr().expect("bound address");
+    let reaper = UdpSocket::bind("127.0.0.1:0").expect("binding sender");
+    let mut listener = OscListener::<_, 8, 1024>::new(StdUdp(socket));
+
+    reaper
+        .send_to(&float_message("/play", 1.), address)
+        .expect("sending");
+    let status = block_on(listener.receive()).expect("valid datagram");
+    assert_eq!(status.transport.play_state, PlayState::Playing);
+
+    reaper.send_to(b"garbage", address).expect("sending");
+    assert!(block_on(listener.receive()).is_err());
+
+    reaper
+        .send_to(&float_message("/track/1/vu", 1.), address)
+        .expect("sending");
+    let status = block_on(listener.receive()).expect("still usable after garbage");
+    assert_eq!(status.tracks.len(), 1);
+    assert_eq!(status.transport.play_state, PlayState::Playing);
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+/// Something that goes wrong with a single response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// waits this long before answering, e.g. to run into the client timeout
+    Delay(Duration),
+    /// announces the full `Content-Length`, but closes the connection after this many bytes of the body
+    TruncateBody(usize),
+    /// inserts a line that no parser can make sense of before the real response
+    MalformedLine,
+    /// closes the connection without answering at all
+    Disconnect,
+}
+
+/// Applies `fault` to every `every`th request, counting from the first request the server gets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultRule {
+    pub every: usize,
+    pub fault: Fault,
+}
+
+impl FaultRule {
+    pub fn always(fault: Fault) -> Self {
+        Self { every: 1, fault }
+    }
+
+    /// whether the rule hits the request with the 1-based `request_number`
+    pub fn applies_to(&self, request_number: usize) -> bool {
+        self.every != 0 && request_number.is_multiple_of(self.every)
+    }
+}
+
+/// The faults to apply to a single response, in the order they happen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Faults {
+    pub delay: Duration,
+    pub truncate_body: Option<usize>,
+    pub malformed_line: bool,
+    pub disconnect: bool,
+}
+
+impl Faults {
+    pub fn for_request(rules: &[FaultRule], request_number: usize) -> Self {
+        rules
+            .iter()
+            .filter(|rule| rule.applies_to(request_number))
+            .fold(Self::default(), |faults, rule| match rule.fault {
+                Fault::Delay(delay) => Self {
+                    delay: faults.delay + delay,
+                    ..faults
+                },
+                Fault::TruncateBody(length) => Self {
+                    truncate_body: Some(faults.truncate_body.map_or(length, |truncated| truncated.min(length))),
+                    ..faults
+                },
+                Fault::MalformedLine => Self {
+                    malformed_line: true,
+                    ..faults
+                },
+                Fault::Disconnect => Self { disconnect: true, ..faults },
+            })
+    }
+}
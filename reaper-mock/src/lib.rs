@@ -0,0 +1,10 @@
+//! A stand-in for Reaper's web interface, serving a scripted [`VirtualProject`] so the client,
+//! firmware and renderer can be exercised on a host without a DAW.
+
+mod faults;
+mod project;
+mod server;
+
+pub use faults::{Fault, FaultRule};
+pub use project::{MeterAnimation, ScriptStep, VirtualProject, VirtualTrack};
+pub use server::{MockServer, RunningServer};
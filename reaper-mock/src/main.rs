@@ -0,0 +1,75 @@
+use reaper_mock::{Fault, FaultRule, MockServer, VirtualProject};
+use std::time::Duration;
+
+const USAGE: &str = "\
+usage: reaper-mock [ADDRESS] [OPTIONS]
+
+serves the demo project on ADDRESS (default 0.0.0.0:8080)
+
+options:
+  --slow-ms MILLIS        delay every response
+  --slow-every N          only delay every Nth response (default 1)
+  --truncate-every N      cut the body of every Nth response short
+  --malformed-every N     prepend a malformed line to every Nth response
+  --disconnect-every N    drop the connection instead of answering every Nth request
+";
+
+fn parse_args() -> Result<(String, Vec<FaultRule>), String> {
+    let mut address = "0.0.0.0:8080".to_owned();
+    let mut faults = Vec::new();
+    let (mut slow, mut slow_every) = (None, 1);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = |name: &str| {
+            args.next()
+                .and_then(|value| value.parse::<u64>().ok())
+                .ok_or_else(|| format!("{name} takes a number"))
+        };
+        match arg.as_str() {
+            "--slow-ms" => slow = Some(Duration::from_millis(number("--slow-ms")?)),
+            "--slow-every" => slow_every = number("--slow-every")? as usize,
+            "--truncate-every" => faults.push(FaultRule {
+                every: number("--truncate-every")? as usize,
+                // enough to get past the TRANSPORT line, but not through the tracks
+                fault: Fault::TruncateBody(64),
+            }),
+            "--malformed-every" => faults.push(FaultRule {
+                every: number("--malformed-every")? as usize,
+                fault: Fault::MalformedLine,
+            }),
+            "--disconnect-every" => faults.push(FaultRule {
+                every: number("--disconnect-every")? as usize,
+                fault: Fault::Disconnect,
+            }),
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            other if other.starts_with('-') => return Err(format!("unknown option {other}\n\n{USAGE}")),
+            other => other.clone_into(&mut address),
+        }
+    }
+    if let Some(delay) = slow {
+        faults.push(FaultRule {
+            every: slow_every,
+            fault: Fault::Delay(delay),
+        });
+    }
+    Ok((address, faults))
+}
+
+fn main() {
+    let (address, faults) = parse_args().unwrap_or_else(|message| {
+        eprintln!("{message}");
+        std::process::exit(2);
+    });
+    let server = MockServer::bind(&address, VirtualProject::demo(), faults).unwrap_or_else(|error| {
+        eprintln!("binding {address}: {error}");
+        std::process::exit(1);
+    });
+    match server.local_addr() {
+        Ok(address) => eprintln!("serving the demo project on http://{address}/_/"),
+        Err(error) => eprintln!("serving the demo project: {error}"),
+    }
+    if let Err(error) = server.run() {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
+}
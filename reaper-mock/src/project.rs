@@ -0,0 +1,273 @@
+use reaper::{truncated, BeatPosition, Decibel, LinearGain, Marker, PlayState, RawTrackFlags, Region, TrackColor, TrackData, TrackFlags, TransportData};
+use std::{f32::consts::TAU, fmt::Write as _, time::Duration};
+
+/// How a track meter moves over time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MeterAnimation {
+    Constant(Decibel),
+    /// swings between `floor` and `ceiling`, `phase` is a fraction of the period
+    Sine { floor: Decibel, ceiling: Decibel, period: Duration, phase: f32 },
+    /// silence, except for a `length` long burst at `level` once every `every`
+    Burst { level: Decibel, every: Duration, length: Duration },
+}
+
+impl Default for MeterAnimation {
+    fn default() -> Self {
+        Self::Constant(Decibel::SILENCE)
+    }
+}
+
+impl MeterAnimation {
+    pub fn level(&self, elapsed: Duration) -> Decibel {
+        match *self {
+            Self::Constant(level) => level,
+            Self::Sine { floor, ceiling, period, phase } => {
+                let cycle = elapsed.as_secs_f32() / period.as_secs_f32().max(f32::EPSILON) + phase;
+                let fraction = 0.5 + 0.5 * (cycle * TAU).sin();
+                Decibel::from_db(floor.as_db() + fraction * (ceiling.as_db() - floor.as_db()))
+            }
+            Self::Burst { level, every, length } => match elapsed.as_nanos() % every.as_nanos().max(1) < length.as_nanos() {
+                true => level,
+                false => Decibel::SILENCE,
+            },
+        }
+    }
+}
+
+/// A track of the [`VirtualProject`], its track number is its position in [`VirtualProject::tracks`] plus one.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualTrack {
+    pub name: String,
+    pub flags: RawTrackFlags,
+    pub volume: LinearGain,
+    pub color: TrackColor,
+    pub folder_depth: i8,
+    pub meter: MeterAnimation,
+}
+
+impl VirtualTrack {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            volume: LinearGain::UNITY,
+            ..Default::default()
+        }
+    }
+}
+
+/// The play state changes to `play_state` once `at` has passed since the start of the script.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScriptStep {
+    pub at: Duration,
+    pub play_state: PlayState,
+}
+
+/// Everything the mock server knows about, the state at any point in time is derived from it.
+#[derive(Debug, Clone)]
+pub struct VirtualProject {
+    pub tracks: Vec<VirtualTrack>,
+    pub master_meter: MeterAnimation,
+    pub tempo: f64,
+    pub time_signature: (u8, u8),
+    pub repeat_on: bool,
+    pub markers: Vec<Marker>,
+    pub regions: Vec<Region>,
+    /// play state changes over time, sorted by [`ScriptStep::at`]
+    pub script: Vec<ScriptStep>,
+    /// starts the script over after this long, `None` stays in the last step
+    pub script_period: Option<Duration>,
+}
+
+impl Default for VirtualProject {
+    fn default() -> Self {
+        Self {
+            tracks: Vec::new(),
+            master_meter: MeterAnimation::default(),
+            tempo: 120.,
+            time_signature: (4, 4),
+            repeat_on: false,
+            markers: Vec::new(),
+            regions: Vec::new(),
+            script: Vec::new(),
+            script_period: None,
+        }
+    }
+}
+
+fn sine(floor: i16, ceiling: i16, period_millis: u64, phase: f32) -> MeterAnimation {
+    MeterAnimation::Sine {
+        floor: Decibel(floor),
+        ceiling: Decibel(ceiling),
+        period: Duration::from_millis(period_millis),
+        phase,
+    }
+}
+
+impl VirtualProject {
+    /// a small band in a drums folder, looping through stopped, playing and recording
+    pub fn demo() -> Self {
+        let track = |name, folder_depth, meter| VirtualTrack {
+            folder_depth,
+            meter,
+            ..VirtualTrack::new(name)
+        };
+        Self {
+            tracks: vec![
+                track("Drums", 1, sine(-300, -60, 500, 0.)),
+                track("Kick", 0, sine(-400, -30, 500, 0.)),
+                track("Snare", 0, sine(-450, -80, 500, 0.5)),
+                VirtualTrack {
+                    flags: RawTrackFlags(TrackFlags::Muted as u16),
+                    ..track("Toms", -1, sine(-600, -200, 2000, 0.))
+                },
+                track("Bass", 0, sine(-250, -120, 1000, 0.25)),
+                VirtualTrack {
+                    flags: RawTrackFlags(TrackFlags::RecordArmed as u16),
+                    color: TrackColor::from_rgb((255, 0, 128), Default::default()),
+                    ..track("Vocals", 0, sine(-350, -100, 3000, 0.))
+                },
+                track(
+                    "Crash",
+                    0,
+                    MeterAnimation::Burst {
+                        level: Decibel(10),
+                        every: Duration::from_secs(4),
+                        length: Duration::from_millis(100),
+                    },
+                ),
+            ],
+            master_meter: sine(-200, -30, 500, 0.),
+            markers: vec![Marker {
+                name: truncated("drop"),
+                id: 1,
+                position_seconds: 8.,
+                ..Default::default()
+            }],
+            regions: vec![Region {
+                name: truncated("verse"),
+                id: 1,
+                start_seconds: 0.,
+                end_seconds: 16.,
+                ..Default::default()
+            }],
+            script: vec![
+                ScriptStep {
+                    at: Duration::ZERO,
+                    play_state: PlayState::Stopped,
+                },
+                ScriptStep {
+                    at: Duration::from_secs(2),
+                    play_state: PlayState::Playing,
+                },
+                ScriptStep {
+                    at: Duration::from_secs(10),
+                    play_state: PlayState::Recording,
+                },
+            ],
+            script_period: Some(Duration::from_secs(15)),
+            ..Default::default()
+        }
+    }
+
+    /// the step of the script that is active `elapsed` after the start, as an index into [`VirtualProject::script`]
+    pub fn script_step(&self, elapsed: Duration) -> Option<usize> {
+        let elapsed = match self.script_period {
+            Some(period) if !period.is_zero() => Duration::from_nanos((elapsed.as_nanos() % period.as_nanos()) as u64),
+            _ => elapsed,
+        };
+        self.script
+            .iter()
+            .rposition(|step| step.at <= elapsed)
+    }
+
+    fn track_data(&self, track_number: u16, elapsed: Duration) -> Option<TrackData> {
+        let (name, flags, volume, color, meter, folder_depth) = match track_number {
+            0 => ("MASTER", RawTrackFlags::default(), LinearGain::UNITY, TrackColor::NONE, &self.master_meter, 0),
+            track_number => self
+                .tracks
+                .get(track_number as usize - 1)
+                .map(|track| (track.name.as_str(), track.flags, track.volume, track.color, &track.meter, track.folder_depth))?,
+        };
+        let level = meter.level(elapsed);
+        Some(TrackData {
+            track_number,
+            name: truncated(name),
+            flags,
+            volume,
+            last_meter_peak: level,
+            last_meter_pos: level,
+            width: 1.,
+            pan_mode: 3,
+            hw_out_count: (track_number == 0) as u16,
+            color,
+            folder_depth: Some(folder_depth),
+            ..Default::default()
+        })
+    }
+
+    /// the project track count, without the master track
+    pub fn track_count(&self) -> u16 {
+        self.tracks.len() as u16
+    }
+
+    pub(crate) fn write_tracks(&self, out: &mut String, first: u16, last: u16, elapsed: Duration) {
+        (first..=last.min(self.track_count()))
+            .filter_map(|track_number| self.track_data(track_number, elapsed))
+            .for_each(|track| {
+                track.write(out).ok();
+                out.push('\n');
+            });
+    }
+
+    pub(crate) fn write_folder_depth(&self, out: &mut String, track_number: u16) {
+        if let Some(track) = self.track_data(track_number, Duration::ZERO) {
+            writeln!(out, "GET/TRACK/{track_number}/I_FOLDERDEPTH\t{}", track.folder_depth.unwrap_or_default()).ok();
+        }
+    }
+
+    pub(crate) fn write_transport(&self, out: &mut String, play_state: PlayState, position_seconds: f64) {
+        let beat_position = self.beat_position(play_state, position_seconds);
+        TransportData {
+            play_state,
+            position_seconds,
+            repeat_on: self.repeat_on,
+            position_string: truncated(&format!("{}:{:06.3}", (position_seconds / 60.) as u64, position_seconds % 60.)),
+            position_string_beats: truncated(&format!("{}.{}.00", beat_position.bar(), beat_position.beat())),
+        }
+        .write(out)
+        .ok();
+        out.push('\n');
+    }
+
+    pub(crate) fn beat_position(&self, play_state: PlayState, position_seconds: f64) -> BeatPosition {
+        let (numerator, denominator) = self.time_signature;
+        let full_beat_position = position_seconds * self.tempo / 60.;
+        BeatPosition {
+            play_state,
+            position_seconds,
+            full_beat_position,
+            measure_count: (full_beat_position / numerator as f64) as i32,
+            beats_in_measure: full_beat_position % numerator as f64,
+            time_signature_numerator: numerator,
+            time_signature_denominator: denominator,
+        }
+    }
+
+    pub(crate) fn write_markers(&self, out: &mut String) {
+        out.push_str("MARKER_LIST\n");
+        self.markers.iter().for_each(|marker| {
+            marker.write(out).ok();
+            out.push('\n');
+        });
+        out.push_str("MARKER_LIST_END\n");
+    }
+
+    pub(crate) fn write_regions(&self, out: &mut String) {
+        out.push_str("REGION_LIST\n");
+        self.regions.iter().for_each(|region| {
+            region.write(out).ok();
+            out.push('\n');
+        });
+        out.push_str("REGION_LIST_END\n");
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    faults::{FaultRule, Faults},
+    project::VirtualProject,
+};
+use reaper::PlayState;
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
+/// a line of the response that fails to parse as any known command
+const MALFORMED_LINE: &str = "TRACK\tnot-a-track-number\n";
+
+/// Transport state that commands and the script change while the server runs.
+#[derive(Debug)]
+struct Transport {
+    play_state: PlayState,
+    position_seconds: f64,
+    updated_at: Instant,
+    /// `(script cycle, step)` applied last, so each step only fires once per cycle
+    script_step: Option<(u128, usize)>,
+}
+
+impl Transport {
+    fn advance(&mut self, project: &VirtualProject, started_at: Instant, now: Instant) {
+        if self.play_state.is_rolling() {
+            self.position_seconds += now.duration_since(self.updated_at).as_secs_f64();
+        }
+        self.updated_at = now;
+
+        let elapsed = now.duration_since(started_at);
+        let cycle = project
+            .script_period
+            .filter(|period| !period.is_zero())
+            .map_or(0, |period| elapsed.as_nanos() / period.as_nanos());
+        let step = project
+            .script_step(elapsed)
+            .map(|step| (cycle, step));
+        if step != self.script_step {
+            self.script_step = step;
+            if let Some((_, step)) = step {
+                self.set_play_state(project.script[step].play_state);
+            }
+        }
+    }
+
+    fn set_play_state(&mut self, play_state: PlayState) {
+        if play_state == PlayState::Stopped {
+            self.position_seconds = 0.;
+        }
+        self.play_state = play_state;
+    }
+}
+
+struct Shared {
+    project: VirtualProject,
+    faults: Vec<FaultRule>,
+    started_at: Instant,
+    transport: Mutex<Transport>,
+    requests: AtomicUsize,
+}
+
+/// Serves the web interface of a [`VirtualProject`] over plain HTTP/1.1, one thread per connection.
+pub struct MockServer {
+    listener: TcpListener,
+    shared: Arc<Shared>,
+}
+
+/// A [`MockServer`] running in the background.
+pub struct RunningServer {
+    pub address: SocketAddr,
+    shared: Arc<Shared>,
+}
+
+impl RunningServer {
+    /// the `ESP_REAPER_BASE_URL` to point a client at
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// how many requests were answered (or failed on purpose) so far
+    pub fn request_count(&self) -> usize {
+        self.shared.requests.load(Ordering::SeqCst)
+    }
+}
+
+impl MockServer {
+    pub fn bind(address: impl ToSocketAddrs, project: VirtualProject, faults: Vec<FaultRule>) -> io::Result<Self> {
+        let now = Instant::now();
+        TcpListener::bind(address).map(|listener| Self {
+            listener,
+            shared: Arc::new(Shared {
+                project,
+                faults,
+                started_at: now,
+                transport: Mutex::new(Transport {
+                    play_state: PlayState::Stopped,
+                    position_seconds: 0.,
+                    updated_at: now,
+                    script_step: None,
+                }),
+                requests: AtomicUsize::new(0),
+            }),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// accepts connections forever
+    pub fn run(self) -> io::Result<()> {
+        self.listener.incoming().try_for_each(|stream| {
+            let shared = self.shared.clone();
+            let stream = stream?;
+            thread::spawn(move || {
+                if let Err(error) = serve_connection(&shared, stream) {
+                    eprintln!("connection failed: {error}");
+                }
+            });
+            Ok(())
+        })
+    }
+
+    pub fn spawn(self) -> io::Result<RunningServer> {
+        let running = RunningServer {
+            address: self.local_addr()?,
+            shared: self.shared.clone(),
+        };
+        thread::spawn(move || self.run());
+        Ok(running)
+    }
+}
+
+/// answers requests on a keep-alive connection until the client goes away or a fault ends it
+fn serve_connection(shared: &Shared, stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+        let mut close = false;
+        loop {
+            let mut header = String::new();
+            match reader.read_line(&mut header)? {
+                0 => return Ok(()),
+                _ if header.trim().is_empty() => break,
+                _ => close |= header.to_ascii_lowercase().trim() == "connection: close",
+            }
+        }
+        let path = request_line
+            .split(' ')
+            .nth(1)
+            .unwrap_or_default();
+
+        let request_number = shared.requests.fetch_add(1, Ordering::SeqCst) + 1;
+        let faults = Faults::for_request(&shared.faults, request_number);
+        thread::sleep(faults.delay);
+        if faults.disconnect {
+            return Ok(());
+        }
+
+        let body = match path.strip_prefix("/_/") {
+            Some(commands) => {
+                let mut body = String::new();
+                if faults.malformed_line {
+                    body.push_str(MALFORMED_LINE);
+                }
+                respond(shared, commands, &mut body);
+                body
+            }
+            None => {
+                write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")?;
+                continue;
+            }
+        };
+
+        write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\r\n", body.len())?;
+        match faults.truncate_body {
+            Some(length) => {
+                stream.write_all(&body.as_bytes()[..length.min(body.len())])?;
+                return stream.flush();
+            }
+            None => stream.write_all(body.as_bytes())?,
+        }
+        stream.flush()?;
+        if close {
+            return Ok(());
+        }
+    }
+}
+
+/// runs every `;` separated command, unknown commands are ignored just like Reaper does
+fn respond(shared: &Shared, commands: &str, body: &mut String) {
+    let project = &shared.project;
+    let mut transport = shared
+        .transport
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    transport.advance(project, shared.started_at, Instant::now());
+    let elapsed = shared.started_at.elapsed();
+    let last_track = project.track_count();
+
+    commands.split(';').for_each(|command| {
+        match command.split('/').collect::<Vec<_>>().as_slice() {
+            ["TRANSPORT"] => project.write_transport(body, transport.play_state, transport.position_seconds),
+            ["BEATPOS"] => {
+                project
+                    .beat_position(transport.play_state, transport.position_seconds)
+                    .write(body)
+                    .ok();
+                body.push('\n');
+            }
+            ["NTRACK"] => body.push_str(&format!("NTRACK\t{last_track}\n")),
+            ["TRACK"] => project.write_tracks(body, 0, last_track, elapsed),
+            ["TRACK", range] => {
+                let range = match range.split_once('-') {
+                    Some((first, last)) => first.parse().ok().zip(last.parse().ok()),
+                    None => range.parse().ok().map(|track| (track, track)),
+                };
+                if let Some((first, last)) = range {
+                    project.write_tracks(body, first, last, elapsed);
+                }
+            }
+            ["GET", "TRACK", track, "I_FOLDERDEPTH"] => {
+                if let Ok(track) = track.parse() {
+                    project.write_folder_depth(body, track);
+                }
+            }
+            ["MARKER_LIST"] => project.write_markers(body),
+            ["REGION_LIST"] => project.write_regions(body),
+            // the transport actions of `ReaperCommand`
+            ["1007"] => transport.set_play_state(PlayState::Playing),
+            ["1016"] => transport.set_play_state(PlayState::Stopped),
+            ["1013"] => transport.set_play_state(PlayState::Recording),
+            ["1008"] => {
+                let paused = match transport.play_state {
+                    PlayState::Paused => PlayState::Playing,
+                    PlayState::Recording => PlayState::RecordPaused,
+                    PlayState::RecordPaused => PlayState::Recording,
+                    _ => PlayState::Paused,
+                };
+                transport.set_play_state(paused);
+            }
+            _ => {}
+        }
+    });
+}
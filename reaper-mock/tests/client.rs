@@ -0,0 +1,65 @@
+use reaper::{PlayState, ReaperCommand};
+use reaper_client::{
+    std_adapter::{block_on, StdDns, StdTcp},
+    ReaperClient,
+};
+use reaper_mock::{Fault, FaultRule, MockServer, VirtualProject};
+use reqwless::client::HttpClient;
+
+type Client<'a> = ReaperClient<'a, StdTcp, 16, 256, 1024, 256, 512>;
+/// reads the body in small chunks, so a response doesn't arrive in one read
+type SmallBufferClient<'a> = ReaperClient<'a, StdTcp, 16, 256, 256, 64, 512>;
+
+#[test]
+fn client_talks_to_the_demo_project() {
+    let server = MockServer::bind("127.0.0.1:0", VirtualProject::demo(), Vec::new())
+        .and_then(MockServer::spawn)
+        .expect("starting the mock server");
+    let base_url = server.base_url();
+
+    let (tcp, dns) = (StdTcp, StdDns);
+    let mut http_client = HttpClient::new(&tcp, &dns);
+    let status = block_on(async {
+        let mut client = Client::new(&mut http_client, &base_url)
+            .await
+            .expect("connecting");
+        client
+            .send_command(&ReaperCommand::Play)
+            .await
+            .expect("pressing play");
+        client
+            .refresh_folder_depths()
+            .await
+            .expect("fetching folder depths");
+        client.get_status().await.expect("fetching the status")
+    });
+
+    assert_eq!(status.transport.play_state, PlayState::Playing);
+    assert_eq!(status.tracks[1].name.as_str(), "Drums");
+    assert_eq!(status.tracks[1].folder_depth, Some(1));
+    assert_eq!(status.folder_tree().children(1).count(), 3);
+}
+
+#[test]
+fn client_reports_malformed_responses() {
+    let malformed_every_second = FaultRule {
+        every: 2,
+        fault: Fault::MalformedLine,
+    };
+    let server = MockServer::bind("127.0.0.1:0", VirtualProject::demo(), vec![malformed_every_second])
+        .and_then(MockServer::spawn)
+        .expect("starting the mock server");
+    let base_url = server.base_url();
+
+    let (tcp, dns) = (StdTcp, StdDns);
+    let mut http_client = HttpClient::new(&tcp, &dns);
+    block_on(async {
+        let mut client = SmallBufferClient::new(&mut http_client, &base_url)
+            .await
+            .expect("connecting");
+        assert!(client.get_status().await.is_ok());
+        assert!(client.get_status().await.is_err());
+        // the rest of the malformed response was read, so the next one comes over the same connection
+        assert!(client.get_status().await.is_ok());
+    });
+}
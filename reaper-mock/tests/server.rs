@@ -0,0 +1,143 @@
+use reaper::{PlayState, RawTrackFlags, ReaperStatus, TrackFlags};
+use reaper_mock::{Fault, FaultRule, MeterAnimation, MockServer, RunningServer, ScriptStep, VirtualProject, VirtualTrack};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+
+type Status = ReaperStatus<16>;
+
+fn start(project: VirtualProject, faults: Vec<FaultRule>) -> RunningServer {
+    MockServer::bind("127.0.0.1:0", project, faults)
+        .and_then(MockServer::spawn)
+        .expect("starting the mock server")
+}
+
+/// the announced `Content-Length` and whatever body arrived before the connection closed
+fn get(server: &RunningServer, path: &str) -> (usize, String) {
+    let mut stream = TcpStream::connect(server.address).expect("connecting");
+    write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").expect("requesting");
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+    let mut header = String::new();
+    while reader.read_line(&mut header).expect("reading header") > 0 && header != "\r\n" {
+        if let Some(length) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+            content_length = length.trim().parse().expect("content length");
+        }
+        header.clear();
+    }
+    let mut body = String::new();
+    reader.read_to_string(&mut body).expect("reading body");
+    (content_length, body)
+}
+
+fn project() -> VirtualProject {
+    VirtualProject {
+        tracks: vec![
+            VirtualTrack {
+                meter: MeterAnimation::Constant(reaper::Decibel(-120)),
+                ..VirtualTrack::new("Kick")
+            },
+            VirtualTrack {
+                flags: RawTrackFlags(TrackFlags::Muted as u16),
+                folder_depth: -1,
+                ..VirtualTrack::new("Snare")
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn serves_a_status_the_parser_accepts() {
+    let server = start(project(), Vec::new());
+    let (content_length, body) = get(&server, "/_/TRANSPORT;BEATPOS;NTRACK;TRACK/0-7");
+    assert_eq!(content_length, body.len());
+
+    let status = Status::parse(&body).expect("parsing the mock response");
+    assert_eq!(status.transport.play_state, PlayState::Stopped);
+    assert_eq!(status.track_count, Some(2));
+    assert_eq!(status.tracks.len(), 3);
+    assert_eq!(status.tracks[1].name.as_str(), "Kick");
+    assert_eq!(status.tracks[1].last_meter_peak, reaper::Decibel(-120));
+    assert!(status.tracks[2].flags.contains(TrackFlags::Muted));
+}
+
+#[test]
+fn answers_folder_depths_and_ignores_unknown_commands() {
+    let server = start(project(), Vec::new());
+    let (_, body) = get(&server, "/_/SET/TRACK/1/SOLO/1;GET/TRACK/2/I_FOLDERDEPTH;GET/TRACK/9/I_FOLDERDEPTH");
+    assert_eq!(body, "GET/TRACK/2/I_FOLDERDEPTH\t-1\n");
+}
+
+#[test]
+fn transport_actions_change_the_play_state() {
+    let server = start(project(), Vec::new());
+    let play_state = |path| {
+        Status::parse(&get(&server, path).1)
+            .expect("parsing the mock response")
+            .transport
+            .play_state
+    };
+    assert_eq!(play_state("/_/1007;TRANSPORT"), PlayState::Playing);
+    assert_eq!(play_state("/_/1008;TRANSPORT"), PlayState::Paused);
+    assert_eq!(play_state("/_/1013;TRANSPORT"), PlayState::Recording);
+    assert_eq!(play_state("/_/1016;TRANSPORT"), PlayState::Stopped);
+}
+
+#[test]
+fn follows_the_script() {
+    let server = start(
+        VirtualProject {
+            script: vec![
+                ScriptStep {
+                    at: Duration::ZERO,
+                    play_state: PlayState::Playing,
+                },
+                ScriptStep {
+                    at: Duration::from_millis(200),
+                    play_state: PlayState::Stopped,
+                },
+            ],
+            ..project()
+        },
+        Vec::new(),
+    );
+    let status = || Status::parse(&get(&server, "/_/TRANSPORT").1).expect("parsing the mock response");
+    let started = Instant::now();
+    assert_eq!(status().transport.play_state, PlayState::Playing);
+    std::thread::sleep(Duration::from_millis(250).saturating_sub(started.elapsed()));
+    assert_eq!(status().transport.play_state, PlayState::Stopped);
+}
+
+#[test]
+fn injects_faults_on_the_configured_requests() {
+    let server = start(
+        project(),
+        vec![
+            FaultRule {
+                every: 2,
+                fault: Fault::MalformedLine,
+            },
+            FaultRule {
+                every: 3,
+                fault: Fault::TruncateBody(10),
+            },
+            FaultRule {
+                every: 5,
+                fault: Fault::Disconnect,
+            },
+        ],
+    );
+    let responses = (0..5)
+        .map(|_| get(&server, "/_/TRANSPORT;TRACK"))
+        .collect::<Vec<_>>();
+
+    assert!(Status::parse(&responses[0].1).is_ok());
+    assert!(Status::parse(&responses[1].1).is_err(), "malformed line is rejected");
+    assert_eq!(responses[2].1.len(), 10);
+    assert!(responses[2].0 > 10, "truncated body still announces its full length");
+    assert_eq!(responses[4], (0, String::new()));
+    assert_eq!(server.request_count(), 5);
+}
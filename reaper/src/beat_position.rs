@@ -0,0 +1,81 @@
+use crate::{parse_field, trim_line, PlayState};
+use embedded_wrap_err::Result;
+use heapless::Vec;
+use tap::Pipe;
+
+/// Musical time as reported by the `BEATPOS` command.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BeatPosition {
+    pub play_state: PlayState,
+    pub position_seconds: f64,
+    pub full_beat_position: f64,
+    /// zero-based index of the current measure
+    pub measure_count: i32,
+    /// zero-based, fractional beat within the current measure
+    pub beats_in_measure: f64,
+    pub time_signature_numerator: u8,
+    pub time_signature_denominator: u8,
+}
+
+impl BeatPosition {
+    /// parses a single `BEATPOS\tplaystate\tposition_seconds\tfull_beat_position\tmeasure_cnt\tbeats_in_measure\tts_numerator\tts_denominator` line
+    pub fn parse(line: &str) -> Result<Self> {
+        match trim_line(line).split('\t').collect::<Vec<&str, 10>>().as_slice() {
+            [marker, play_state, position_seconds, full_beat_position, measure_count, beats_in_measure, time_signature_numerator, time_signature_denominator] => {
+                marker
+                    .trim()
+                    .eq("BEATPOS")
+                    .then_some(())
+                    .ok_or("expected BEATPOS")?;
+                Ok(Self {
+                    play_state: parse_field::<u8>(play_state, "parsing play_state value").map(PlayState::from_repr)?,
+                    position_seconds: parse_field(position_seconds, "invalid position_seconds")?,
+                    full_beat_position: parse_field(full_beat_position, "invalid full_beat_position")?,
+                    measure_count: parse_field(measure_count, "invalid measure_cnt")?,
+                    beats_in_measure: parse_field(beats_in_measure, "invalid beats_in_measure")?,
+                    time_signature_numerator: parse_field(time_signature_numerator, "invalid ts_numerator")?,
+                    time_signature_denominator: parse_field(time_signature_denominator, "invalid ts_denominator")?,
+                })
+            }
+            _ => Err("expected BEATPOS with 7 fields"),
+        }
+    }
+
+    /// writes the line in the exact format [`BeatPosition::parse`] expects, without the trailing newline
+    pub fn write(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        write!(
+            out,
+            "BEATPOS\t{}\t{:.6}\t{:.6}\t{}\t{:.6}\t{}\t{}",
+            self.play_state.repr(),
+            self.position_seconds,
+            self.full_beat_position,
+            self.measure_count,
+            self.beats_in_measure,
+            self.time_signature_numerator,
+            self.time_signature_denominator
+        )
+    }
+
+    /// one-based bar number, as shown in Reaper's ruler
+    pub fn bar(&self) -> i32 {
+        self.measure_count + 1
+    }
+
+    /// one-based beat number within the current bar
+    pub fn beat(&self) -> u8 {
+        (self.beats_in_measure.max(0.) as u8).saturating_add(1)
+    }
+
+    /// how far into the current beat we are, in `0.0..1.0`
+    pub fn beat_phase(&self) -> f64 {
+        self.beats_in_measure.max(0.).pipe(|beats| beats - (beats as u32) as f64)
+    }
+
+    /// average beats per minute since the project start, not the tempo at the current position
+    /// once the project has a tempo change. `None` at the start of the project and before it
+    pub fn average_tempo(&self) -> Option<f64> {
+        (self.position_seconds > 0. && self.full_beat_position > 0.).then(|| self.full_beat_position * 60. / self.position_seconds)
+    }
+}
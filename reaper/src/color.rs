@@ -0,0 +1,50 @@
+use tap::Pipe;
+
+/// Byte order of the OS-native color integer Reaper reports.
+///
+/// Windows uses a `COLORREF` (`0x00BBGGRR`), while SWELL on macOS/Linux packs
+/// the channels the other way around (`0x00RRGGBB`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorByteOrder {
+    #[default]
+    Bgr,
+    Rgb,
+}
+
+/// Raw track color exactly as sent by the web interface.
+///
+/// `0` means "no color", and every custom color has [`TrackColor::CUSTOM_COLOR_FLAG`] set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackColor(pub u32);
+
+impl TrackColor {
+    pub const CUSTOM_COLOR_FLAG: u32 = 0x100_0000;
+    pub const NONE: Self = Self(0);
+
+    pub fn is_set(self) -> bool {
+        self.0 & Self::CUSTOM_COLOR_FLAG != 0
+    }
+
+    /// decodes the color into `(red, green, blue)`, or `None` if the track uses the theme default
+    pub fn rgb(self, order: ColorByteOrder) -> Option<(u8, u8, u8)> {
+        self.is_set().then(|| {
+            let [low, middle, high, _flags] = self.0.to_le_bytes();
+            match order {
+                ColorByteOrder::Bgr => (low, middle, high),
+                ColorByteOrder::Rgb => (high, middle, low),
+            }
+        })
+    }
+
+    pub fn from_rgb((red, green, blue): (u8, u8, u8), order: ColorByteOrder) -> Self {
+        match order {
+            ColorByteOrder::Bgr => u32::from_le_bytes([red, green, blue, 0]),
+            ColorByteOrder::Rgb => u32::from_le_bytes([blue, green, red, 0]),
+        }
+        .pipe(|color| Self(color | Self::CUSTOM_COLOR_FLAG))
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{ActionId, LinearGain};
+use core::fmt::Write;
+use embedded_wrap_err::{IntoWrapErrDebugExt, Result};
+use heapless::String;
+
+/// New state of a track toggle such as mute or solo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Switch {
+    Toggle,
+    Off,
+    On,
+}
+
+impl Switch {
+    fn repr(self) -> i8 {
+        match self {
+            Self::Toggle => -1,
+            Self::Off => 0,
+            Self::On => 1,
+        }
+    }
+}
+
+/// A command that changes something in Reaper, serialized to a web interface path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReaperCommand<'a> {
+    Play,
+    Stop,
+    Record,
+    Pause,
+    Mute { track: u16, state: Switch },
+    Solo { track: u16, state: Switch },
+    RecordArm { track: u16, state: Switch },
+    Select { track: u16, state: Switch },
+    Volume { track: u16, volume: LinearGain },
+    /// `-1.0` is hard left, `1.0` hard right
+    Pan { track: u16, pan: f32 },
+    /// moves the edit cursor (and the playhead when rolling)
+    SetPosition { position_seconds: f64 },
+    Action(#[cfg_attr(feature = "serde", serde(borrow))] ActionId<'a>),
+}
+
+impl<'a> ReaperCommand<'a> {
+    pub const PLAY_ACTION: u32 = 1007;
+    pub const STOP_ACTION: u32 = 1016;
+    pub const RECORD_ACTION: u32 = 1013;
+    pub const PAUSE_ACTION: u32 = 1008;
+
+    pub fn write(&self, out: &mut impl Write) -> core::fmt::Result {
+        match self {
+            Self::Play => ActionId::Numeric(Self::PLAY_ACTION).write(out),
+            Self::Stop => ActionId::Numeric(Self::STOP_ACTION).write(out),
+            Self::Record => ActionId::Numeric(Self::RECORD_ACTION).write(out),
+            Self::Pause => ActionId::Numeric(Self::PAUSE_ACTION).write(out),
+            Self::Mute { track, state } => write!(out, "SET/TRACK/{track}/MUTE/{}", state.repr()),
+            Self::Solo { track, state } => write!(out, "SET/TRACK/{track}/SOLO/{}", state.repr()),
+            Self::RecordArm { track, state } => write!(out, "SET/TRACK/{track}/RECARM/{}", state.repr()),
+            Self::Select { track, state } => write!(out, "SET/TRACK/{track}/SEL/{}", state.repr()),
+            Self::Volume { track, volume } => write!(out, "SET/TRACK/{track}/VOL/{:.6}", volume.0),
+            Self::Pan { track, pan } => write!(out, "SET/TRACK/{track}/PAN/{pan:.6}"),
+            Self::SetPosition { position_seconds } => write!(out, "SET/POS/{position_seconds:.6}"),
+            Self::Action(action) => action.write(out),
+        }
+    }
+
+    pub fn write_path(&self, out: &mut impl Write) -> Result<()> {
+        out.write_str("/_/")
+            .and_then(|_| self.write(out))
+            .into_wrap_err_dbg("building url string")
+    }
+
+    pub fn to_path<const SIZE: usize>(&self) -> Result<String<SIZE>> {
+        let mut path = String::new();
+        self.write_path(&mut path).map(|_| path)
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{PlayState, RawTrackFlags, ReaperStatus, TrackData, TrackFlags};
+use tap::Pipe;
+
+/// A single thing that changed between two consecutive [`ReaperStatus`] snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StatusChange {
+    PlayState { previous: PlayState, current: PlayState },
+    Repeat { repeat_on: bool },
+    TrackCount { previous: usize, current: usize },
+    /// a track number the previous snapshot didn't have
+    TrackAdded { track_number: u16 },
+    /// a track number of the previous snapshot that is gone now
+    TrackRemoved { track_number: u16 },
+    TrackFlags { track_number: u16, added: RawTrackFlags, removed: RawTrackFlags },
+    /// the track went over 0dBFS after not clipping in the previous snapshot
+    ClipStarted { track_number: u16 },
+}
+
+impl StatusChange {
+    /// `flag` got set on the track, e.g. "track 5 got armed"
+    pub fn track_flag_added(&self, flag: TrackFlags) -> Option<u16> {
+        match self {
+            Self::TrackFlags { track_number, added, .. } if added.contains(flag) => Some(*track_number),
+            _ => None,
+        }
+    }
+
+    /// `flag` got cleared on the track
+    pub fn track_flag_removed(&self, flag: TrackFlags) -> Option<u16> {
+        match self {
+            Self::TrackFlags { track_number, removed, .. } if removed.contains(flag) => Some(*track_number),
+            _ => None,
+        }
+    }
+}
+
+fn track_changes(previous: Option<&TrackData>, current: &TrackData) -> [Option<StatusChange>; 3] {
+    let track_number = current.track_number;
+    let added = previous
+        .is_none()
+        .then_some(StatusChange::TrackAdded { track_number });
+    let flags = previous
+        .map(|previous| (previous.flags.0, current.flags.0))
+        .filter(|(previous, current)| previous != current)
+        .map(|(previous, current)| StatusChange::TrackFlags {
+            track_number,
+            added: RawTrackFlags(current & !previous),
+            removed: RawTrackFlags(previous & !current),
+        });
+    let clip = (current.is_clipping() && !previous.is_some_and(TrackData::is_clipping)).then_some(StatusChange::ClipStarted { track_number });
+    [added, flags, clip]
+}
+
+impl<const MAX_TRACK_COUNT: usize> ReaperStatus<MAX_TRACK_COUNT> {
+    /// finds a track by its Reaper track number (`0` is the master track)
+    pub fn track(&self, track_number: u16) -> Option<&TrackData> {
+        self.tracks
+            .iter()
+            .find(|track| track.track_number == track_number)
+    }
+
+    /// everything that changed since `previous`, tracks are matched by their track number.
+    /// Reaper renumbers the tracks after a removed one, so that shows up as the last track being removed
+    pub fn diff<'a>(&'a self, previous: &'a Self) -> impl Iterator<Item = StatusChange> + 'a {
+        let play_state = (previous.transport.play_state != self.transport.play_state).then_some(StatusChange::PlayState {
+            previous: previous.transport.play_state,
+            current: self.transport.play_state,
+        });
+        let repeat = (previous.transport.repeat_on != self.transport.repeat_on).then_some(StatusChange::Repeat {
+            repeat_on: self.transport.repeat_on,
+        });
+        let (previous_count, current_count) = (previous.total_track_count(), self.total_track_count());
+        let track_count = (previous_count != current_count).then_some(StatusChange::TrackCount {
+            previous: previous_count,
+            current: current_count,
+        });
+        [play_state, repeat, track_count]
+            .into_iter()
+            .chain(
+                self.tracks
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(index, current)| {
+                        // tracks usually keep their position, so only search when they don't
+                        previous
+                            .tracks
+                            .get(index)
+                            .filter(|previous| previous.track_number == current.track_number)
+                            .or_else(|| previous.track(current.track_number))
+                            .pipe(|previous| track_changes(previous, current))
+                    }),
+            )
+            .flatten()
+            .chain(
+                previous
+                    .tracks
+                    .iter()
+                    .filter(move |previous| self.track(previous.track_number).is_none())
+                    .map(|previous| StatusChange::TrackRemoved {
+                        track_number: previous.track_number,
+                    }),
+            )
+    }
+
+    /// `NTRACK` if it was requested, otherwise the number of tracks received
+    pub fn total_track_count(&self) -> usize {
+        self.track_count
+            .map(usize::from)
+            .unwrap_or(self.tracks.len())
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{parse_field, trim_line, ReaperStatus, TrackData, TrackFlags};
+use embedded_wrap_err::Result;
+use heapless::Vec;
+
+pub(crate) const TRACK_PARAMETER_PREFIX: &str = "GET/TRACK/";
+const FOLDER_DEPTH_PARAMETER: &str = "I_FOLDERDEPTH";
+
+/// parses a `GET/TRACK/tracknumber/I_FOLDERDEPTH\tdepth` line, `None` if it is about another track parameter
+pub(crate) fn parse_folder_depth(line: &str) -> Option<Result<(u16, i8)>> {
+    let (path, value) = trim_line(line).split_once('\t')?;
+    let (track_number, parameter) = path
+        .trim()
+        .strip_prefix(TRACK_PARAMETER_PREFIX)?
+        .split_once('/')?;
+    (parameter == FOLDER_DEPTH_PARAMETER).then(|| {
+        // the value is a double on Reaper's side and may come with decimals
+        let depth = parse_field::<f64>(value, "invalid I_FOLDERDEPTH")?;
+        Ok((parse_field(track_number, "invalid tracknumber")?, depth as i8))
+    })
+}
+
+/// writes the line in the exact format [`parse_folder_depth`] expects, without the trailing newline
+pub(crate) fn write_folder_depth(out: &mut impl core::fmt::Write, track_number: u16, depth: i8) -> core::fmt::Result {
+    write!(out, "{TRACK_PARAMETER_PREFIX}{track_number}/{FOLDER_DEPTH_PARAMETER}\t{depth}")
+}
+
+/// Where a single track sits in the folder hierarchy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FolderNode {
+    pub track_number: u16,
+    /// position of the track in [`ReaperStatus::tracks`]
+    pub index: usize,
+    /// track number of the folder this track sits in, `None` at the top level
+    pub parent: Option<u16>,
+    /// how many folders this track is nested in
+    pub level: u8,
+    pub is_folder: bool,
+}
+
+/// The folder hierarchy of a [`ReaperStatus`], rebuilt from the `I_FOLDERDEPTH` of every track.
+///
+/// `I_FOLDERDEPTH` is `1` for a folder, `0` for a regular track and `-n` for
+/// the last track of `n` nested folders. Tracks without a known depth are
+/// treated as regular tracks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FolderTree<const MAX_TRACK_COUNT: usize> {
+    nodes: Vec<FolderNode, MAX_TRACK_COUNT>,
+}
+
+impl<const MAX_TRACK_COUNT: usize> FolderTree<MAX_TRACK_COUNT> {
+    pub fn new(tracks: &[TrackData]) -> Self {
+        let mut open_folders = Vec::<u16, MAX_TRACK_COUNT>::new();
+        let nodes = tracks
+            .iter()
+            .enumerate()
+            .map(|(index, track)| {
+                let depth = track.folder_depth.unwrap_or_default();
+                let node = FolderNode {
+                    track_number: track.track_number,
+                    index,
+                    parent: open_folders.last().copied(),
+                    level: open_folders.len().min(u8::MAX as usize) as u8,
+                    is_folder: depth > 0 || track.flags.contains(TrackFlags::Folder),
+                };
+                match depth {
+                    1.. => {
+                        open_folders.push(track.track_number).ok();
+                    }
+                    0 => {}
+                    closed => {
+                        let remaining = open_folders.len().saturating_sub(closed.unsigned_abs() as usize);
+                        open_folders.truncate(remaining);
+                    }
+                }
+                node
+            })
+            .take(MAX_TRACK_COUNT)
+            .collect();
+        Self { nodes }
+    }
+
+    /// every track in project order
+    pub fn nodes(&self) -> &[FolderNode] {
+        &self.nodes
+    }
+
+    pub fn node(&self, track_number: u16) -> Option<&FolderNode> {
+        self.nodes
+            .iter()
+            .find(|node| node.track_number == track_number)
+    }
+
+    /// tracks sitting directly in the folder `track_number`
+    pub fn children(&self, track_number: u16) -> impl Iterator<Item = &FolderNode> + '_ {
+        self.nodes
+            .iter()
+            .filter(move |node| node.parent == Some(track_number))
+    }
+
+    /// tracks that are not in any folder
+    pub fn roots(&self) -> impl Iterator<Item = &FolderNode> + '_ {
+        self.nodes.iter().filter(|node| node.parent.is_none())
+    }
+
+    /// the top level folder the track at `index` is routed into, the track itself for a top level folder
+    /// and `None` for a track outside of any folder
+    pub fn bus(&self, index: usize) -> Option<&FolderNode> {
+        self.nodes
+            .get(..=index)?
+            .iter()
+            .rev()
+            .find(|node| node.level == 0)
+            .filter(|node| node.is_folder)
+    }
+
+    /// tracks nested at most `max_level` folders deep, i.e. every deeper folder collapsed into its folder track
+    pub fn visible(&self, max_level: u8) -> impl Iterator<Item = &FolderNode> + '_ {
+        self.nodes
+            .iter()
+            .filter(move |node| node.level <= max_level)
+    }
+
+    /// the deepest `max_level` for [`FolderTree::visible`] that shows at most `columns` tracks,
+    /// `None` if even the top level doesn't fit
+    pub fn deepest_level_fitting(&self, columns: usize) -> Option<u8> {
+        let deepest = self.nodes.iter().map(|node| node.level).max()?;
+        (0..=deepest)
+            .rev()
+            .find(|max_level| self.visible(*max_level).count() <= columns)
+    }
+}
+
+/// The last known `I_FOLDERDEPTH` of every track by track number.
+///
+/// Reaper only reports it for an explicit `GET/TRACK/n/I_FOLDERDEPTH` per track, so it is
+/// refreshed once in a while and filled into the statuses polled in between.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FolderDepths<const MAX_TRACK_COUNT: usize> {
+    /// indexed by track number, only as long as the highest track seen so far
+    depths: Vec<Option<i8>, MAX_TRACK_COUNT>,
+}
+
+impl<const MAX_TRACK_COUNT: usize> FolderDepths<MAX_TRACK_COUNT> {
+    pub const fn new() -> Self {
+        Self { depths: Vec::new() }
+    }
+
+    pub fn get(&self, track_number: u16) -> Option<i8> {
+        self.depths
+            .get(track_number as usize)
+            .copied()
+            .flatten()
+    }
+
+    /// keeps the depths of `status`, the response to a folder depth query for the tracks `first..=last`.
+    /// Returns `false` if the project ends within that range, the tracks past its end are forgotten.
+    pub fn update(&mut self, first: u16, last: u16, status: &ReaperStatus<MAX_TRACK_COUNT>) -> bool {
+        status.tracks.iter().for_each(|track| {
+            let index = track.track_number as usize;
+            if index >= self.depths.len() {
+                // tracks past `MAX_TRACK_COUNT` never make it into a status anyway
+                self.depths.resize(index + 1, None).ok();
+            }
+            if let Some(depth) = self.depths.get_mut(index) {
+                *depth = track.folder_depth;
+            }
+        });
+        let requested = (last as usize + 1).saturating_sub(first as usize);
+        let project_goes_on = status.tracks.len() >= requested;
+        if !project_goes_on {
+            self.depths.truncate(first as usize + status.tracks.len());
+        }
+        project_goes_on
+    }
+
+    /// fills in the depth of every track of `status` that came without one
+    pub fn apply(&self, status: &mut ReaperStatus<MAX_TRACK_COUNT>) {
+        status
+            .tracks
+            .iter_mut()
+            .filter(|track| track.folder_depth.is_none())
+            .for_each(|track| track.folder_depth = self.get(track.track_number));
+    }
+}
+
+impl<const MAX_TRACK_COUNT: usize> ReaperStatus<MAX_TRACK_COUNT> {
+    pub fn folder_tree(&self) -> FolderTree<MAX_TRACK_COUNT> {
+        FolderTree::new(&self.tracks)
+    }
+}
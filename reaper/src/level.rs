@@ -0,0 +1,77 @@
+use core::fmt;
+
+/// Level in tenths of a decibel, the fixed point unit of the web interface meter fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Decibel(pub i16);
+
+impl Decibel {
+    pub const ZERO: Self = Self(0);
+    /// what Reaper reports for a silent meter
+    pub const SILENCE: Self = Self(-1500);
+
+    pub fn from_db(db: f32) -> Self {
+        Self(libm::roundf(db * 10.).clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+    }
+
+    pub fn as_db(self) -> f32 {
+        self.0 as f32 / 10.
+    }
+
+    /// anything at or above 0dBFS
+    pub fn is_clipping(self) -> bool {
+        self >= Self::ZERO
+    }
+
+    pub fn to_gain(self) -> LinearGain {
+        match self <= Self::SILENCE {
+            true => LinearGain::SILENCE,
+            false => LinearGain(libm::powf(10., self.as_db() / 20.)),
+        }
+    }
+
+    /// position of this level on a meter spanning `floor..=0dB`, clamped to `0.0..=1.0`
+    pub fn meter_fraction(self, floor: Decibel) -> f32 {
+        match floor.0 {
+            0.. => 0.,
+            floor => 1. - (self.0.clamp(floor, 0) as f32 / floor as f32),
+        }
+    }
+}
+
+impl fmt::Display for Decibel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1}dB", self.as_db())
+    }
+}
+
+/// Linear amplitude factor, as used by the web interface volume field (`1.0` is 0dB).
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearGain(pub f32);
+
+impl LinearGain {
+    pub const UNITY: Self = Self(1.);
+    pub const SILENCE: Self = Self(0.);
+
+    pub fn to_decibel(self) -> Decibel {
+        match self.0 > 0. {
+            true => Decibel::from_db(20. * libm::log10f(self.0)).max(Decibel::SILENCE),
+            false => Decibel::SILENCE,
+        }
+    }
+}
+
+impl From<Decibel> for LinearGain {
+    fn from(decibel: Decibel) -> Self {
+        decibel.to_gain()
+    }
+}
+
+impl From<LinearGain> for Decibel {
+    fn from(gain: LinearGain) -> Self {
+        gain.to_decibel()
+    }
+}
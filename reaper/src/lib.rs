@@ -1,116 +1,310 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use embedded_wrap_err::{IntoWrapErrDebugExt, IntoWrapErrExt as _, Result, WrapErrorExt as _};
+use embedded_wrap_err::{IntoWrapErrDebugExt, Result};
 use enumflags2::BitFlags;
-use heapless::Vec;
+use heapless::{String, Vec};
 use tap::prelude::*;
 
-impl<const MAX_TRACK_COUNT: usize> ReaperStatus<MAX_TRACK_COUNT> {
-    pub fn parse(response: &str) -> Result<Self> {
-        response.trim().lines().pipe(|mut lines| {
-            lines
-                .next()
-                .ok_or("empty response")
-                .into_wrap_err("empty response")
-                .and_then(|transport| {
-                    transport
-                        .split_once('\t')
-                        .map(|(a, b)| (a.trim(), b.trim()))
-                        .ok_or("expected at least one tab")
-                        .and_then(|(marker, rest)| {
-                            marker
-                                .eq("TRANSPORT")
-                                .then_some(rest)
-                                .ok_or("expected TRANSPORT")
-                        })
-                        .and_then(|transport| transport.split('\t').next().ok_or("empty transport?"))
-                        .into_wrap_err("parsing TRANSPORT")
-                        .and_then(|play_state| {
-                            play_state
-                                .parse::<u8>()
-                                .into_wrap_err_dbg("parsing play_state value")
-                                .and_then(|repr| PlayState::from_repr(repr).into_wrap_err("bad playstate"))
-                        })
-                        .and_then(|play_state| {
-                            lines
-                                .map(|line| -> Result<TrackData> {
-                                    match line.split('\t').collect::<Vec<&str, 64>>().as_slice() {
-                                        [_track, _tracknumber, _trackname, trackflags, _volume, _pan, last_meter_peak, last_meter_pos, _width_pan2, _panmode, _sendcnt, _recvcnt, _hwoutcnt, _color] => {
-                                            let flags = trackflags
-                                                .trim()
-                                                .parse::<u16>()
-                                                .into_wrap_err_dbg("invalid trackflags number")
-                                                .and_then(|repr| BitFlags::<TrackFlags, u16>::try_from(repr).into_wrap_err_dbg("bad TrackFlags"))
-                                                .wrap_err("reading flags")?;
-                                            let last_meter_peak = last_meter_peak
-                                                .trim()
-                                                .parse::<i16>()
-                                                .into_wrap_err_dbg("invalid last_meter_peak")?;
-                                            let last_meter_pos = last_meter_pos
-                                                .trim()
-                                                .parse::<i16>()
-                                                .into_wrap_err_dbg("invalid last_meter_pos")?;
-                                            Ok(TrackData { flags, last_meter_peak, last_meter_pos })
-                                        }
-                                        _ => Err("come on... forgot to change the input size?"),
-                                    }
-                                })
-                                .take(MAX_TRACK_COUNT)
-                                .collect::<Result<Vec<_, MAX_TRACK_COUNT>>>()
-                                .wrap_err("extracting track data")
-                                .map(|tracks| ReaperStatus { play_state, tracks })
-                        })
+mod beat_position;
+mod color;
+mod command;
+mod diff;
+mod folders;
+mod level;
+mod markers;
+mod osc;
+mod query;
+mod recording;
+mod stream;
+
+pub use beat_position::BeatPosition;
+pub use color::{ColorByteOrder, TrackColor};
+pub use command::{ReaperCommand, Switch};
+pub use diff::StatusChange;
+pub use folders::{FolderDepths, FolderNode, FolderTree};
+pub use level::{Decibel, LinearGain};
+pub use markers::{Marker, MarkerList, MarkerName, Region, MAX_MARKER_COUNT, MAX_MARKER_NAME_SIZE, MAX_REGION_COUNT};
+pub use osc::{for_each_osc_message, DatagramSource, OscAccumulator, OscArgument, OscListener, OscMessage};
+pub use query::{ActionId, QueryCommand, ReaperQuery, MAX_QUERY_COMMANDS};
+pub use recording::{RecordedResponse, RecordedResponses, Replay};
+pub use stream::{Command, Record, ResponseParser, StatusBuilder};
+
+pub const MAX_POSITION_STRING_SIZE: usize = 32;
+
+pub type PositionString = String<MAX_POSITION_STRING_SIZE>;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransportData {
+    pub play_state: PlayState,
+    pub position_seconds: f64,
+    pub repeat_on: bool,
+    pub position_string: PositionString,
+    pub position_string_beats: PositionString,
+}
+
+impl TransportData {
+    /// parses a single `TRANSPORT\tplaystate\tposition_seconds\tisRepeatOn\tposition_string\tposition_string_beats` line
+    pub fn parse(line: &str) -> Result<Self> {
+        match trim_line(line).split('\t').collect::<Vec<&str, 8>>().as_slice() {
+            [marker, play_state, position_seconds, repeat_on, position_string, position_string_beats] => {
+                marker
+                    .trim()
+                    .eq("TRANSPORT")
+                    .then_some(())
+                    .ok_or("expected TRANSPORT")?;
+                let play_state = parse_field::<u8>(play_state, "parsing play_state value").map(PlayState::from_repr)?;
+                let position_seconds = parse_field::<f64>(position_seconds, "invalid position_seconds")?;
+                let repeat_on = parse_field::<u8>(repeat_on, "invalid repeat_on").map(|repeat_on| repeat_on != 0)?;
+                let position_string = position_string
+                    .trim()
+                    .pipe(PositionString::try_from)
+                    .into_wrap_err_dbg("position_string too long")?;
+                let position_string_beats = position_string_beats
+                    .trim()
+                    .pipe(PositionString::try_from)
+                    .into_wrap_err_dbg("position_string_beats too long")?;
+                Ok(Self {
+                    play_state,
+                    position_seconds,
+                    repeat_on,
+                    position_string,
+                    position_string_beats,
                 })
-        })
+            }
+            _ => Err("expected TRANSPORT with 5 fields"),
+        }
+    }
+
+    /// writes the line in the exact format [`TransportData::parse`] expects, without the trailing newline
+    pub fn write(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        write!(
+            out,
+            "TRANSPORT\t{}\t{:.6}\t{}\t{}\t{}",
+            self.play_state.repr(),
+            self.position_seconds,
+            self.repeat_on as u8,
+            self.position_string,
+            self.position_string_beats
+        )
     }
 }
 
-#[derive(Debug)]
+pub const MAX_TRACK_NAME_SIZE: usize = 16;
+
+pub type TrackName = String<MAX_TRACK_NAME_SIZE>;
+
+/// copies as much of `value` as fits, never splitting a character
+pub fn truncated<const SIZE: usize>(value: &str) -> String<SIZE> {
+    String::new().tap_mut(|out| {
+        value
+            .chars()
+            .try_for_each(|c| out.push(c))
+            .ok();
+    })
+}
+
+/// like [`truncated`], but also undoes the `\t`, `\n` and `\\` escaping the web interface applies to names
+pub(crate) fn unescaped<const SIZE: usize>(value: &str) -> String<SIZE> {
+    String::new().tap_mut(|out| {
+        let mut chars = value.chars();
+        core::iter::from_fn(|| match chars.next()? {
+            '\\' => match chars.next() {
+                Some('t') => Some('\t'),
+                Some('n') => Some('\n'),
+                Some(other) => Some(other),
+                None => Some('\\'),
+            },
+            other => Some(other),
+        })
+        .try_for_each(|c| out.push(c))
+        .ok();
+    })
+}
+
+/// the inverse of [`unescaped`]
+pub(crate) fn write_escaped_name(out: &mut impl core::fmt::Write, name: &str) -> core::fmt::Result {
+    name.chars().try_for_each(|c| match c {
+        '\t' => out.write_str("\\t"),
+        '\n' => out.write_str("\\n"),
+        '\\' => out.write_str("\\\\"),
+        c => out.write_char(c),
+    })
+}
+
+/// strips line endings and padding, but never the tabs that separate (possibly empty) trailing fields
+pub(crate) fn trim_line(line: &str) -> &str {
+    line.trim_matches(|c| c == '\r' || c == '\n' || c == ' ')
+}
+
+pub(crate) fn parse_field<T>(value: &str, context: &'static str) -> Result<T>
+where
+    T: core::str::FromStr,
+    T::Err: core::fmt::Debug,
+{
+    value.trim().parse::<T>().into_wrap_err_dbg(context)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrackData {
-    pub flags: BitFlags<TrackFlags>,
-    pub last_meter_peak: i16,
-    pub last_meter_pos: i16,
+    pub track_number: u16,
+    pub name: TrackName,
+    pub flags: RawTrackFlags,
+    pub volume: LinearGain,
+    pub pan: f32,
+    pub last_meter_peak: Decibel,
+    pub last_meter_pos: Decibel,
+    pub width: f32,
+    pub pan_mode: u8,
+    pub send_count: u16,
+    pub receive_count: u16,
+    pub hw_out_count: u16,
+    pub color: TrackColor,
+    /// `I_FOLDERDEPTH`, which is not part of the `TRACK` line and only known if it was queried separately
+    pub folder_depth: Option<i8>,
+}
+
+impl TrackData {
+    /// parses a single 14-column `TRACK\ttracknumber\ttrackname\t...\tcolor` line
+    pub fn parse(line: &str) -> Result<Self> {
+        match line.split('\t').collect::<Vec<&str, 16>>().as_slice() {
+            [marker, track_number, name, flags, volume, pan, last_meter_peak, last_meter_pos, width, pan_mode, send_count, receive_count, hw_out_count, color] => {
+                marker
+                    .trim()
+                    .eq("TRACK")
+                    .then_some(())
+                    .ok_or("expected TRACK")?;
+                let flags = parse_field::<u16>(flags, "invalid trackflags number").map(RawTrackFlags)?;
+                Ok(Self {
+                    track_number: parse_field(track_number, "invalid tracknumber")?,
+                    name: unescaped(name),
+                    flags,
+                    volume: parse_field(volume, "invalid volume").map(LinearGain)?,
+                    pan: parse_field(pan, "invalid pan")?,
+                    last_meter_peak: parse_field(last_meter_peak, "invalid last_meter_peak").map(Decibel)?,
+                    last_meter_pos: parse_field(last_meter_pos, "invalid last_meter_pos").map(Decibel)?,
+                    width: parse_field(width, "invalid width/pan2")?,
+                    pan_mode: parse_field(pan_mode, "invalid panmode")?,
+                    send_count: parse_field(send_count, "invalid sendcnt")?,
+                    receive_count: parse_field(receive_count, "invalid recvcnt")?,
+                    hw_out_count: parse_field(hw_out_count, "invalid hwoutcnt")?,
+                    color: parse_field(color, "invalid color").map(TrackColor)?,
+                    folder_depth: None,
+                })
+            }
+            _ => Err("expected TRACK with 13 fields"),
+        }
+    }
+
+    /// either meter value went over 0dBFS
+    pub fn is_clipping(&self) -> bool {
+        self.last_meter_pos.is_clipping() || self.last_meter_peak.is_clipping()
+    }
+
+    /// writes the line in the exact format [`TrackData::parse`] expects, without the trailing newline
+    pub fn write(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        write!(out, "TRACK\t{}\t", self.track_number)
+            .and_then(|_| write_escaped_name(out, &self.name))
+            .and_then(|_| {
+                write!(
+                    out,
+                    "\t{}\t{:.6}\t{:.6}\t{}\t{}\t{:.6}\t{}\t{}\t{}\t{}\t{}",
+                    self.flags.0,
+                    self.volume.0,
+                    self.pan,
+                    self.last_meter_peak.0,
+                    self.last_meter_pos.0,
+                    self.width,
+                    self.pan_mode,
+                    self.send_count,
+                    self.receive_count,
+                    self.hw_out_count,
+                    self.color.0
+                )
+            })
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
-#[repr(u8)]
+/// The web interface `playstate`: `&1` playing, `&2` paused, `&4` recording.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlayState {
     #[default]
-    Stopped = 0,
-    Playing = 1,
-    Paused = 3,
-    Recording = 5,
-    RecordPaused = 6,
+    Stopped,
+    Playing,
+    Paused,
+    Recording,
+    RecordPaused,
+    /// anything a future Reaper might send, kept so it can be written back unchanged
+    Unknown(u8),
 }
+
 impl PlayState {
-    pub fn from_repr(repr: u8) -> Result<Self> {
+    pub fn repr(self) -> u8 {
+        match self {
+            Self::Stopped => 0,
+            Self::Playing => 1,
+            Self::Paused => 2,
+            Self::Recording => 5,
+            Self::RecordPaused => 6,
+            Self::Unknown(repr) => repr,
+        }
+    }
+
+    pub fn from_repr(repr: u8) -> Self {
         match repr {
-            i if i == Self::Stopped as u8 => Ok(Self::Stopped),
-            i if i == Self::Playing as u8 => Ok(Self::Playing),
-            i if i == Self::Paused as u8 => Ok(Self::Paused),
-            i if i == Self::Recording as u8 => Ok(Self::Recording),
-            i if i == Self::RecordPaused as u8 => Ok(Self::RecordPaused),
-            _unknown => Err("unknown variant"),
+            0 => Self::Stopped,
+            1 => Self::Playing,
+            2 => Self::Paused,
+            5 => Self::Recording,
+            6 => Self::RecordPaused,
+            unknown => Self::Unknown(unknown),
         }
     }
+
+    /// the playhead is moving
+    pub fn is_rolling(self) -> bool {
+        matches!(self, Self::Playing | Self::Recording)
+    }
+
+    /// recording, including a paused recording
+    pub fn is_recording(self) -> bool {
+        matches!(self, Self::Recording | Self::RecordPaused)
+    }
+
+    pub fn is_paused(self) -> bool {
+        matches!(self, Self::Paused | Self::RecordPaused)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReaperStatus<const MAX_TRACK_COUNT: usize> {
-    pub play_state: PlayState,
+    pub transport: TransportData,
+    pub beat_position: Option<BeatPosition>,
+    /// total track count as reported by `NTRACK`, which can exceed `MAX_TRACK_COUNT`
+    pub track_count: Option<u16>,
     pub tracks: heapless::Vec<TrackData, MAX_TRACK_COUNT>,
 }
 
 impl<const MAX_TRACK_COUNT: usize> core::default::Default for ReaperStatus<MAX_TRACK_COUNT> {
     fn default() -> Self {
         ReaperStatus {
-            play_state: Default::default(),
+            transport: Default::default(),
+            beat_position: None,
+            track_count: None,
             tracks: Vec::new().tap_mut(|tracks| {
                 (0..MAX_TRACK_COUNT)
                     .map(|offset| TrackData {
-                        flags: Default::default(),
-                        last_meter_peak: -750 + 10 * (offset as i16),
-                        last_meter_pos: -250 + 10 * (offset as i16),
+                        track_number: offset as u16 + 1,
+                        last_meter_peak: Decibel(-750 + 10 * (offset as i16)),
+                        last_meter_pos: Decibel(-250 + 10 * (offset as i16)),
+                        ..Default::default()
                     })
                     .try_for_each(|e| tracks.push(e))
                     .expect("not enough space in vec")
@@ -119,9 +313,12 @@ impl<const MAX_TRACK_COUNT: usize> core::default::Default for ReaperStatus<MAX_T
     }
 }
 
+/// Every documented bit of the web interface `trackflags` field.
 #[enumflags2::bitflags]
 #[repr(u16)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TrackFlags {
     Folder = 1,
     Selected = 2,
@@ -132,5 +329,41 @@ pub enum TrackFlags {
     RecordArmed = 64,
     RecordMonitoringOn = 128,
     RecordMonitoringAuto = 256,
-    Unknown1 = 512,
+    HiddenFromTcp = 512,
+    HiddenFromMcp = 1024,
+}
+
+/// `trackflags` exactly as sent, so bits added by newer Reaper builds never fail the whole status.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawTrackFlags(pub u16);
+
+impl RawTrackFlags {
+    /// the bits this crate knows about
+    pub fn known(self) -> BitFlags<TrackFlags> {
+        BitFlags::from_bits_truncate(self.0)
+    }
+
+    /// the bits this crate doesn't know about (yet)
+    pub fn unknown(self) -> u16 {
+        self.0 & !BitFlags::<TrackFlags>::all().bits()
+    }
+
+    pub fn contains(self, flag: TrackFlags) -> bool {
+        self.known().contains(flag)
+    }
+
+    pub fn set(&mut self, flag: TrackFlags, on: bool) {
+        match on {
+            true => self.0 |= flag as u16,
+            false => self.0 &= !(flag as u16),
+        }
+    }
+}
+
+impl From<BitFlags<TrackFlags>> for RawTrackFlags {
+    fn from(flags: BitFlags<TrackFlags>) -> Self {
+        Self(flags.bits())
+    }
 }
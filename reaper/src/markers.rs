@@ -0,0 +1,175 @@
+use crate::{parse_field, trim_line, unescaped, write_escaped_name, Record, TrackColor};
+use embedded_wrap_err::{Result, WrapErrorExt as _};
+use heapless::{String, Vec};
+
+pub const MAX_MARKER_NAME_SIZE: usize = 16;
+pub const MAX_MARKER_COUNT: usize = 16;
+pub const MAX_REGION_COUNT: usize = 16;
+
+pub type MarkerName = String<MAX_MARKER_NAME_SIZE>;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Marker {
+    pub name: MarkerName,
+    pub id: u32,
+    pub position_seconds: f64,
+    pub color: TrackColor,
+}
+
+impl Marker {
+    /// parses a single `MARKER\tname\tmarkerID\tposition\tcolor` line
+    pub fn parse(line: &str) -> Result<Self> {
+        match trim_line(line).split('\t').collect::<Vec<&str, 6>>().as_slice() {
+            [marker, name, id, position_seconds, color] => {
+                marker
+                    .trim()
+                    .eq("MARKER")
+                    .then_some(())
+                    .ok_or("expected MARKER")?;
+                Ok(Self {
+                    name: unescaped(name),
+                    id: parse_field(id, "invalid markerID")?,
+                    position_seconds: parse_field(position_seconds, "invalid position")?,
+                    color: parse_field(color, "invalid color").map(TrackColor)?,
+                })
+            }
+            _ => Err("expected MARKER with 4 fields"),
+        }
+    }
+
+    /// writes the line in the exact format [`Marker::parse`] expects, without the trailing newline
+    pub fn write(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        out.write_str("MARKER\t")
+            .and_then(|_| write_escaped_name(out, &self.name))
+            .and_then(|_| write!(out, "\t{}\t{:.6}\t{}", self.id, self.position_seconds, self.color.0))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Region {
+    pub name: MarkerName,
+    pub id: u32,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub color: TrackColor,
+}
+
+impl Region {
+    /// parses a single `REGION\tname\tregionID\tstart\tend\tcolor` line
+    pub fn parse(line: &str) -> Result<Self> {
+        match trim_line(line).split('\t').collect::<Vec<&str, 7>>().as_slice() {
+            [marker, name, id, start_seconds, end_seconds, color] => {
+                marker
+                    .trim()
+                    .eq("REGION")
+                    .then_some(())
+                    .ok_or("expected REGION")?;
+                Ok(Self {
+                    name: unescaped(name),
+                    id: parse_field(id, "invalid regionID")?,
+                    start_seconds: parse_field(start_seconds, "invalid start")?,
+                    end_seconds: parse_field(end_seconds, "invalid end")?,
+                    color: parse_field(color, "invalid color").map(TrackColor)?,
+                })
+            }
+            _ => Err("expected REGION with 5 fields"),
+        }
+    }
+
+    /// writes the line in the exact format [`Region::parse`] expects, without the trailing newline
+    pub fn write(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        out.write_str("REGION\t")
+            .and_then(|_| write_escaped_name(out, &self.name))
+            .and_then(|_| write!(out, "\t{}\t{:.6}\t{:.6}\t{}", self.id, self.start_seconds, self.end_seconds, self.color.0))
+    }
+
+    pub fn contains(&self, position_seconds: f64) -> bool {
+        (self.start_seconds..self.end_seconds).contains(&position_seconds)
+    }
+}
+
+/// Markers and regions from `MARKER_LIST` / `REGION_LIST`, in the order Reaper sent them.
+///
+/// Kept apart from [`crate::ReaperStatus`], they change rarely and are fetched with their own query.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarkerList {
+    pub markers: Vec<Marker, MAX_MARKER_COUNT>,
+    pub regions: Vec<Region, MAX_REGION_COUNT>,
+}
+
+impl MarkerList {
+    pub const fn new() -> Self {
+        Self {
+            markers: Vec::new(),
+            regions: Vec::new(),
+        }
+    }
+
+    /// parses a `MARKER_LIST;REGION_LIST` response that is already fully in memory
+    pub fn parse(response: &str) -> Result<Self> {
+        response
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .try_fold(Self::new(), |mut list, line| {
+                Record::parse(line)
+                    .and_then(|record| list.push(record))
+                    .map(|_| list)
+            })
+            .wrap_err("extracting markers")
+    }
+
+    /// adds a `MARKER` or `REGION` record and ignores any other one.
+    /// One that doesn't fit is dropped and reported, the list stays usable
+    pub fn push(&mut self, record: Record) -> Result<()> {
+        match record {
+            Record::Marker(marker) => self
+                .markers
+                .push(marker)
+                .map_err(|_| "too many markers"),
+            Record::Region(region) => self
+                .regions
+                .push(region)
+                .map_err(|_| "too many regions"),
+            _ => Ok(()),
+        }
+    }
+
+    /// writes both lists including their `MARKER_LIST`/`REGION_LIST` framing, one line each
+    pub fn write(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        out.write_str("MARKER_LIST\n")
+            .and_then(|_| {
+                self.markers
+                    .iter()
+                    .try_for_each(|marker| marker.write(out).and_then(|_| out.write_char('\n')))
+            })
+            .and_then(|_| out.write_str("MARKER_LIST_END\nREGION_LIST\n"))
+            .and_then(|_| {
+                self.regions
+                    .iter()
+                    .try_for_each(|region| region.write(out).and_then(|_| out.write_char('\n')))
+            })
+            .and_then(|_| out.write_str("REGION_LIST_END\n"))
+    }
+
+    /// the region the playhead is in; for nested regions the one that started last wins
+    pub fn current_region(&self, position_seconds: f64) -> Option<&Region> {
+        self.regions
+            .iter()
+            .filter(|region| region.contains(position_seconds))
+            .max_by(|a, b| a.start_seconds.total_cmp(&b.start_seconds))
+    }
+
+    /// the first marker strictly after the playhead
+    pub fn next_marker(&self, position_seconds: f64) -> Option<&Marker> {
+        self.markers
+            .iter()
+            .filter(|marker| marker.position_seconds > position_seconds)
+            .min_by(|a, b| a.position_seconds.total_cmp(&b.position_seconds))
+    }
+}
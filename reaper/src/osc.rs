@@ -0,0 +1,400 @@
+//! OSC as pushed by Reaper's control surface support, an alternative to polling the web interface.
+//!
+//! [`OscAccumulator`] folds messages of the default pattern config
+//! (`Default.ReaperOSC`) into a [`ReaperStatus`], so everything downstream
+//! works the same whichever transport the status came from.
+
+use crate::{truncated, Decibel, LinearGain, PlayState, ReaperStatus, TrackData, TrackFlags};
+use embedded_wrap_err::{IntoWrapErrDebugExt, Result, WrapErrorExt as _};
+use heapless::Vec;
+use tap::Pipe;
+
+const BUNDLE_TAG: &[u8] = b"#bundle\0";
+/// the longest address the accumulator understands is `/track/N/volume/db`
+const MAX_ADDRESS_SEGMENTS: usize = 5;
+/// Reaper never nests bundles, deeper packets are rejected before they can exhaust the stack
+const MAX_BUNDLE_DEPTH: usize = 4;
+
+/// A single argument of an [`OscMessage`], borrowing from the packet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum OscArgument<'a> {
+    Int(i32),
+    Float(f32),
+    String(&'a str),
+    Blob(&'a [u8]),
+    True,
+    False,
+    Nil,
+    Impulse,
+}
+
+impl<'a> OscArgument<'a> {
+    /// Reaper sends most values as floats, but integers and booleans are accepted as well
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Self::Int(value) => Some(*value as f32),
+            Self::Float(value) => Some(*value),
+            Self::True => Some(1.),
+            Self::False => Some(0.),
+            _ => None,
+        }
+    }
+
+    /// buttons and toggles are floats where anything but `0` means on
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_f32().map(|value| value != 0.)
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded OSC message, arguments are decoded lazily.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub struct OscMessage<'a> {
+    pub address: &'a str,
+    /// type tags without the leading `,`
+    pub type_tags: &'a str,
+    arguments: &'a [u8],
+}
+
+/// splits off a NUL terminated string padded to a multiple of 4 bytes
+fn read_padded_str(bytes: &[u8]) -> Result<(&str, &[u8])> {
+    let length = bytes
+        .iter()
+        .position(|byte| *byte == 0)
+        .ok_or("unterminated OSC string")?;
+    let padded = (length / 4 + 1) * 4;
+    let value = core::str::from_utf8(&bytes[..length]).into_wrap_err_dbg("invalid utf8 in OSC string")?;
+    bytes
+        .get(padded..)
+        .map(|rest| (value, rest))
+        .ok_or("OSC string is missing its padding")
+}
+
+fn read_u32(bytes: &[u8]) -> Result<(u32, &[u8])> {
+    match bytes {
+        [a, b, c, d, rest @ ..] => Ok((u32::from_be_bytes([*a, *b, *c, *d]), rest)),
+        _ => Err("truncated OSC value"),
+    }
+}
+
+impl<'a> OscMessage<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        let (address, rest) = read_padded_str(bytes).wrap_err("reading address")?;
+        address
+            .starts_with('/')
+            .then_some(())
+            .ok_or("OSC address must start with '/'")?;
+        // very old implementations omit the type tags altogether
+        let (type_tags, arguments) = match rest {
+            [] => ("", rest),
+            rest => read_padded_str(rest)
+                .wrap_err("reading type tags")
+                .and_then(|(type_tags, arguments)| {
+                    type_tags
+                        .strip_prefix(',')
+                        .map(|type_tags| (type_tags, arguments))
+                        .ok_or("OSC type tags must start with ','")
+                })?,
+        };
+        Ok(Self { address, type_tags, arguments })
+    }
+
+    pub fn arguments(&self) -> impl Iterator<Item = Result<OscArgument<'a>>> + 'a {
+        let mut bytes = self.arguments;
+        self.type_tags.chars().map(move |tag| {
+            let (argument, rest) = match tag {
+                'i' => read_u32(bytes).map(|(value, rest)| (OscArgument::Int(value as i32), rest))?,
+                'f' => read_u32(bytes).map(|(value, rest)| (OscArgument::Float(f32::from_bits(value)), rest))?,
+                's' => read_padded_str(bytes).map(|(value, rest)| (OscArgument::String(value), rest))?,
+                'b' => {
+                    let (length, rest) = read_u32(bytes)?;
+                    let length = length as usize;
+                    let padded = length.div_ceil(4) * 4;
+                    match (rest.get(..length), rest.get(padded..)) {
+                        (Some(blob), Some(rest)) => (OscArgument::Blob(blob), rest),
+                        _ => return Err("truncated OSC blob"),
+                    }
+                }
+                'T' => (OscArgument::True, bytes),
+                'F' => (OscArgument::False, bytes),
+                'N' => (OscArgument::Nil, bytes),
+                'I' => (OscArgument::Impulse, bytes),
+                _ => return Err("unsupported OSC type tag"),
+            };
+            bytes = rest;
+            Ok(argument)
+        })
+    }
+
+    pub fn first_argument(&self) -> Result<OscArgument<'a>> {
+        self.arguments()
+            .next()
+            .unwrap_or(Err("OSC message has no arguments"))
+    }
+}
+
+/// calls `on_message` for the message in `packet`, or for every message of a (nested) bundle
+pub fn for_each_osc_message<'a>(packet: &'a [u8], on_message: &mut impl FnMut(OscMessage<'a>) -> Result<()>) -> Result<()> {
+    for_each_nested_osc_message(packet, 0, on_message)
+}
+
+fn for_each_nested_osc_message<'a>(packet: &'a [u8], depth: usize, on_message: &mut impl FnMut(OscMessage<'a>) -> Result<()>) -> Result<()> {
+    match packet.strip_prefix(BUNDLE_TAG) {
+        None => OscMessage::parse(packet).and_then(on_message),
+        Some(_) if depth == MAX_BUNDLE_DEPTH => Err("OSC bundles nested too deeply"),
+        Some(bundle) => {
+            // the time tag is ignored, everything is applied as soon as it arrives
+            let mut elements = bundle.get(8..).ok_or("truncated OSC bundle")?;
+            while !elements.is_empty() {
+                let (size, rest) = read_u32(elements).wrap_err("reading bundle element size")?;
+                let element = rest
+                    .get(..size as usize)
+                    .ok_or("truncated OSC bundle element")?;
+                for_each_nested_osc_message(element, depth + 1, on_message)?;
+                elements = &rest[size as usize..];
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Builds a [`ReaperStatus`] out of the messages of Reaper's default OSC pattern config.
+///
+/// Reaper only sends what changed, so the status is kept between packets.
+/// Track numbers in `/track/N/...` are relative to the bank of the control
+/// surface, so its track count should be set to at least `MAX_TRACK_COUNT`.
+#[derive(Debug, Clone)]
+pub struct OscAccumulator<const MAX_TRACK_COUNT: usize> {
+    status: ReaperStatus<MAX_TRACK_COUNT>,
+    playing: bool,
+    paused: bool,
+    recording: bool,
+    /// level a `/track/N/vu` of `0.0` stands for, values in between are mapped linearly in dB
+    /// which is close enough to the (undocumented) meter curve of Reaper
+    pub vu_floor: Decibel,
+    /// level a `/track/N/vu` of `1.0` stands for
+    pub vu_ceiling: Decibel,
+    skipped: usize,
+}
+
+impl<const MAX_TRACK_COUNT: usize> Default for OscAccumulator<MAX_TRACK_COUNT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const MAX_TRACK_COUNT: usize> OscAccumulator<MAX_TRACK_COUNT> {
+    pub fn new() -> Self {
+        Self {
+            status: ReaperStatus {
+                tracks: Vec::new(),
+                ..Default::default()
+            },
+            playing: false,
+            paused: false,
+            recording: false,
+            vu_floor: Decibel(-600),
+            vu_ceiling: Decibel(60),
+            skipped: 0,
+        }
+    }
+
+    /// the status as of the last applied message
+    pub fn status(&self) -> &ReaperStatus<MAX_TRACK_COUNT> {
+        &self.status
+    }
+
+    /// number of messages ignored because their address is not understood
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// applies every message of a received UDP datagram
+    pub fn feed(&mut self, packet: &[u8]) -> Result<()> {
+        for_each_osc_message(packet, &mut |message| self.apply(&message))
+    }
+
+    pub fn apply(&mut self, message: &OscMessage<'_>) -> Result<()> {
+        let segments = message
+            .address
+            .split('/')
+            .skip(1)
+            .take(MAX_ADDRESS_SEGMENTS + 1)
+            .collect::<Vec<&str, { MAX_ADDRESS_SEGMENTS + 1 }>>();
+        let transport = &mut self.status.transport;
+        match segments.as_slice() {
+            ["play"] => self.playing = bool_argument(message)?,
+            ["pause"] => self.paused = bool_argument(message)?,
+            ["record"] => self.recording = bool_argument(message)?,
+            ["stop"] => {
+                if bool_argument(message)? {
+                    self.playing = false;
+                    self.paused = false;
+                    self.recording = false;
+                }
+            }
+            ["repeat"] => transport.repeat_on = bool_argument(message)?,
+            ["time"] => transport.position_seconds = float_argument(message)? as f64,
+            ["time", "str"] => transport.position_string = truncated(str_argument(message)?),
+            ["beat", "str"] => transport.position_string_beats = truncated(str_argument(message)?),
+            ["master", parameter @ ..] => self.apply_track(0, parameter, message)?,
+            ["track", track_number, parameter @ ..] => match track_number.parse::<u16>() {
+                Ok(track_number) => self.apply_track(track_number, parameter, message)?,
+                // e.g. `/track/volume`, which addresses the selected track
+                Err(_) => self.skipped += 1,
+            },
+            _ => self.skipped += 1,
+        }
+        self.status.transport.play_state = match (self.recording, self.playing, self.paused) {
+            (true, _, true) => PlayState::RecordPaused,
+            (true, _, false) => PlayState::Recording,
+            (false, true, _) => PlayState::Playing,
+            (false, false, true) => PlayState::Paused,
+            (false, false, false) => PlayState::Stopped,
+        };
+        Ok(())
+    }
+
+    fn apply_track(&mut self, track_number: u16, parameter: &[&str], message: &OscMessage<'_>) -> Result<()> {
+        // decoded before the track is looked up, so unknown parameters don't create tracks
+        let update = match parameter {
+            ["mute"] => TrackUpdate::Flag(TrackFlags::Muted, bool_argument(message)?),
+            ["solo"] => TrackUpdate::Flag(TrackFlags::Soloed, bool_argument(message)?),
+            ["recarm"] => TrackUpdate::Flag(TrackFlags::RecordArmed, bool_argument(message)?),
+            ["select"] => TrackUpdate::Flag(TrackFlags::Selected, bool_argument(message)?),
+            ["vu"] => {
+                let (vu_floor, vu_ceiling) = (self.vu_floor, self.vu_ceiling);
+                let fraction = float_argument(message)?.clamp(0., 1.);
+                match fraction > 0. {
+                    true => Decibel::from_db(vu_floor.as_db() + fraction * (vu_ceiling.as_db() - vu_floor.as_db())),
+                    false => Decibel::SILENCE,
+                }
+                .pipe(TrackUpdate::Level)
+            }
+            ["name"] => TrackUpdate::Name(str_argument(message)?),
+            ["volume", "db"] => TrackUpdate::Volume(Decibel::from_db(float_argument(message)?).to_gain()),
+            ["pan"] => TrackUpdate::Pan(float_argument(message)? * 2. - 1.),
+            _ => {
+                self.skipped += 1;
+                return Ok(());
+            }
+        };
+        let Some(track) = self.track_mut(track_number) else {
+            // no room left, the message is dropped just like an overflowing `TRACK` line
+            return Ok(());
+        };
+        match update {
+            TrackUpdate::Flag(flag, on) => track.flags.set(flag, on),
+            TrackUpdate::Level(level) => {
+                track.last_meter_pos = level;
+                track.last_meter_peak = level;
+            }
+            TrackUpdate::Name(name) => track.name = truncated(name),
+            TrackUpdate::Volume(volume) => track.volume = volume,
+            TrackUpdate::Pan(pan) => track.pan = pan,
+        }
+        Ok(())
+    }
+
+    /// the track with that number, created in track order when it's not known yet
+    fn track_mut(&mut self, track_number: u16) -> Option<&mut TrackData> {
+        let tracks = &mut self.status.tracks;
+        let index = tracks
+            .iter()
+            .position(|track| track.track_number >= track_number)
+            .unwrap_or(tracks.len());
+        if tracks.get(index).map(|track| track.track_number) != Some(track_number) {
+            tracks
+                .insert(
+                    index,
+                    TrackData {
+                        track_number,
+                        last_meter_pos: Decibel::SILENCE,
+                        last_meter_peak: Decibel::SILENCE,
+                        ..Default::default()
+                    },
+                )
+                .ok()?;
+        }
+        tracks.get_mut(index)
+    }
+}
+
+/// A decoded `/track/N/...` message.
+enum TrackUpdate<'a> {
+    Flag(TrackFlags, bool),
+    Level(Decibel),
+    Name(&'a str),
+    Volume(LinearGain),
+    Pan(f32),
+}
+
+fn float_argument(message: &OscMessage<'_>) -> Result<f32> {
+    message
+        .first_argument()?
+        .as_f32()
+        .ok_or("expected a numeric OSC argument")
+}
+
+fn bool_argument(message: &OscMessage<'_>) -> Result<bool> {
+    message
+        .first_argument()?
+        .as_bool()
+        .ok_or("expected a numeric OSC argument")
+}
+
+fn str_argument<'a>(message: &OscMessage<'a>) -> Result<&'a str> {
+    message
+        .first_argument()?
+        .as_str()
+        .ok_or("expected a string OSC argument")
+}
+
+/// Where OSC datagrams come from, a UDP socket of whatever network stack is around.
+#[allow(async_fn_in_trait)]
+pub trait DatagramSource {
+    /// waits for the next datagram and copies it into `buffer`, returning its length
+    async fn receive(&mut self, buffer: &mut [u8]) -> Result<usize>;
+}
+
+/// Receives OSC datagrams from a [`DatagramSource`] and folds them into a [`ReaperStatus`].
+pub struct OscListener<S, const MAX_TRACK_COUNT: usize, const MAX_DATAGRAM_SIZE: usize> {
+    source: S,
+    buffer: [u8; MAX_DATAGRAM_SIZE],
+    pub accumulator: OscAccumulator<MAX_TRACK_COUNT>,
+}
+
+impl<S, const MAX_TRACK_COUNT: usize, const MAX_DATAGRAM_SIZE: usize> OscListener<S, MAX_TRACK_COUNT, MAX_DATAGRAM_SIZE>
+where
+    S: DatagramSource,
+{
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            buffer: [0; MAX_DATAGRAM_SIZE],
+            accumulator: OscAccumulator::new(),
+        }
+    }
+
+    /// waits for the next datagram and returns the status with it applied,
+    /// a malformed datagram is an error but leaves the listener usable
+    pub async fn receive(&mut self) -> Result<&ReaperStatus<MAX_TRACK_COUNT>> {
+        let length = self
+            .source
+            .receive(&mut self.buffer)
+            .await
+            .wrap_err("receiving OSC datagram")?;
+        self.accumulator
+            .feed(&self.buffer[..length])
+            .wrap_err("decoding OSC datagram")
+            .map(|_| self.accumulator.status())
+    }
+}
@@ -0,0 +1,232 @@
+use core::fmt::Write;
+use embedded_wrap_err::{IntoWrapErrDebugExt, Result};
+use heapless::{String, Vec};
+
+pub const MAX_QUERY_COMMANDS: usize = 16;
+
+/// An action either by its numeric command id or by a named id such as `_SWS_ABOUT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ActionId<'a> {
+    Numeric(u32),
+    Named(&'a str),
+}
+
+/// A single command of a web interface request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QueryCommand<'a> {
+    Transport,
+    BeatPosition,
+    TrackCount,
+    /// every track including the master track (0)
+    AllTracks,
+    Track(u16),
+    /// inclusive range of tracks
+    TrackRange(u16, u16),
+    /// `I_FOLDERDEPTH` of a single track, which the `TRACK` line doesn't carry
+    FolderDepth(u16),
+    ExtState { section: &'a str, key: &'a str },
+    CommandState(#[cfg_attr(feature = "serde", serde(borrow))] ActionId<'a>),
+    MarkerList,
+    RegionList,
+}
+
+impl<'a> QueryCommand<'a> {
+    /// leading tokens of the response lines this command produces
+    pub fn response_commands(&self) -> &'static [&'static str] {
+        match self {
+            Self::Transport => &["TRANSPORT"],
+            Self::BeatPosition => &["BEATPOS"],
+            Self::TrackCount => &["NTRACK"],
+            Self::AllTracks | Self::Track(_) | Self::TrackRange(..) => &["TRACK"],
+            // the actual line starts with the whole `GET/TRACK/n/I_FOLDERDEPTH` path
+            Self::FolderDepth(_) => &["GET/TRACK"],
+            Self::ExtState { .. } => &["EXTSTATE"],
+            Self::CommandState(_) => &["CMDSTATE"],
+            Self::MarkerList => &["MARKER_LIST", "MARKER", "MARKER_LIST_END"],
+            Self::RegionList => &["REGION_LIST", "REGION", "REGION_LIST_END"],
+        }
+    }
+
+    /// upper bound of `TRACK` lines in the response, `None` if it depends on the project
+    pub fn max_track_lines(&self) -> Option<usize> {
+        match self {
+            Self::AllTracks => None,
+            Self::Track(_) => Some(1),
+            Self::TrackRange(first, last) => Some(last.saturating_sub(*first) as usize + 1),
+            _ => Some(0),
+        }
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> core::fmt::Result {
+        match self {
+            Self::Transport => out.write_str("TRANSPORT"),
+            Self::BeatPosition => out.write_str("BEATPOS"),
+            Self::TrackCount => out.write_str("NTRACK"),
+            Self::AllTracks => out.write_str("TRACK"),
+            Self::Track(track) => write!(out, "TRACK/{track}"),
+            Self::TrackRange(first, last) => write!(out, "TRACK/{first}-{last}"),
+            Self::FolderDepth(track) => write!(out, "GET/TRACK/{track}/I_FOLDERDEPTH"),
+            Self::ExtState { section, key } => out
+                .write_str("GET/EXTSTATE/")
+                .and_then(|_| write_escaped(out, section))
+                .and_then(|_| out.write_char('/'))
+                .and_then(|_| write_escaped(out, key)),
+            Self::CommandState(action) => out
+                .write_str("GET/")
+                .and_then(|_| action.write(out)),
+            Self::MarkerList => out.write_str("MARKER_LIST"),
+            Self::RegionList => out.write_str("REGION_LIST"),
+        }
+    }
+}
+
+impl<'a> ActionId<'a> {
+    pub fn write(&self, out: &mut impl Write) -> core::fmt::Result {
+        match self {
+            Self::Numeric(id) => write!(out, "{id}"),
+            Self::Named(name) => write_escaped(out, name),
+        }
+    }
+}
+
+/// percent-encodes everything but unreserved characters, so user supplied names can't inject extra commands
+pub(crate) fn write_escaped(out: &mut impl Write, value: &str) -> core::fmt::Result {
+    value.bytes().try_for_each(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.write_char(byte as char),
+        byte => write!(out, "%{byte:02X}"),
+    })
+}
+
+/// Composes several web interface commands into a single `/_/` request path,
+/// e.g. `ReaperQuery::new().transport().track_range(0, 63)` becomes `/_/TRANSPORT;TRACK/0-63`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReaperQuery<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    commands: Vec<QueryCommand<'a>, MAX_QUERY_COMMANDS>,
+    overflowed: bool,
+}
+
+impl<'a> ReaperQuery<'a> {
+    pub const fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+            overflowed: false,
+        }
+    }
+
+    pub fn command(mut self, command: QueryCommand<'a>) -> Self {
+        if self.commands.push(command).is_err() {
+            self.overflowed = true;
+        }
+        self
+    }
+
+    pub fn transport(self) -> Self {
+        self.command(QueryCommand::Transport)
+    }
+
+    pub fn beat_position(self) -> Self {
+        self.command(QueryCommand::BeatPosition)
+    }
+
+    pub fn track_count(self) -> Self {
+        self.command(QueryCommand::TrackCount)
+    }
+
+    pub fn all_tracks(self) -> Self {
+        self.command(QueryCommand::AllTracks)
+    }
+
+    pub fn track(self, track: u16) -> Self {
+        self.command(QueryCommand::Track(track))
+    }
+
+    pub fn track_range(self, first: u16, last: u16) -> Self {
+        self.command(QueryCommand::TrackRange(first, last))
+    }
+
+    pub fn folder_depth(self, track: u16) -> Self {
+        self.command(QueryCommand::FolderDepth(track))
+    }
+
+    /// one `I_FOLDERDEPTH` command per track of the inclusive range, mind [`MAX_QUERY_COMMANDS`]
+    pub fn folder_depths(self, first: u16, last: u16) -> Self {
+        (first..=last).fold(self, Self::folder_depth)
+    }
+
+    pub fn ext_state(self, section: &'a str, key: &'a str) -> Self {
+        self.command(QueryCommand::ExtState { section, key })
+    }
+
+    pub fn command_state(self, action: ActionId<'a>) -> Self {
+        self.command(QueryCommand::CommandState(action))
+    }
+
+    pub fn marker_list(self) -> Self {
+        self.command(QueryCommand::MarkerList)
+    }
+
+    pub fn region_list(self) -> Self {
+        self.command(QueryCommand::RegionList)
+    }
+
+    pub fn commands(&self) -> &[QueryCommand<'a>] {
+        &self.commands
+    }
+
+    /// whether a response line starting with `command` is an answer to this query
+    pub fn expects(&self, command: &str) -> bool {
+        self.commands.iter().any(|query| {
+            query
+                .response_commands()
+                .iter()
+                .any(|response| command == *response || (command.starts_with(*response) && command[response.len()..].starts_with('/')))
+        })
+    }
+
+    /// upper bound of `TRACK` lines in the response, `None` if it depends on the project
+    pub fn max_track_lines(&self) -> Option<usize> {
+        self.commands
+            .iter()
+            .try_fold(0, |total, command| command.max_track_lines().map(|lines| total + lines))
+    }
+
+    /// fails if the response may contain more tracks than a `ReaperStatus<MAX_TRACK_COUNT>` can keep
+    pub fn check_track_capacity(&self, max_track_count: usize) -> Result<()> {
+        match self.max_track_lines() {
+            Some(lines) if lines <= max_track_count => Ok(()),
+            _ => Err("query may return more tracks than MAX_TRACK_COUNT"),
+        }
+    }
+
+    pub fn write_path(&self, out: &mut impl Write) -> Result<()> {
+        if self.overflowed {
+            return Err("too many commands in query");
+        }
+        out.write_str("/_/")
+            .and_then(|_| {
+                self.commands
+                    .iter()
+                    .enumerate()
+                    .try_for_each(|(index, command)| {
+                        match index {
+                            0 => Ok(()),
+                            _ => out.write_char(';'),
+                        }
+                        .and_then(|_| command.write(out))
+                    })
+            })
+            .into_wrap_err_dbg("building url string")
+    }
+
+    pub fn to_path<const SIZE: usize>(&self) -> Result<String<SIZE>> {
+        let mut path = String::new();
+        self.write_path(&mut path).map(|_| path)
+    }
+}
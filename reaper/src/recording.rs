@@ -0,0 +1,186 @@
+//! A plain text format for captured web interface traffic, and a [`Replay`] that plays it back on its original schedule.
+//!
+//! Every response is a header line `@millis\tbody_length\tpath` followed by `body_length` bytes
+//! of the body exactly as it arrived and a newline, so recordings stay readable and diffable.
+//! Bodies are kept as raw bytes, a response that isn't valid UTF-8 fails on replay just like it did live.
+
+use crate::{parse_field, FolderDepths, ReaperQuery, ReaperStatus};
+use embedded_wrap_err::{IntoWrapErrDebugExt as _, Result, WrapErrorExt as _};
+use heapless::Vec;
+use tap::Pipe as _;
+
+/// One response of a recorded session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedResponse<'a> {
+    /// when the response arrived, counted from the start of the recording
+    pub at_millis: u64,
+    /// the request path, e.g. `/_/TRANSPORT;BEATPOS;TRACK/0-63`
+    pub path: &'a str,
+    pub body: &'a [u8],
+}
+
+impl<'a> RecordedResponse<'a> {
+    #[cfg(feature = "std")]
+    pub fn write(&self, out: &mut impl std::io::Write) -> std::io::Result<()> {
+        writeln!(out, "@{}\t{}\t{}", self.at_millis, self.body.len(), self.path)?;
+        out.write_all(self.body)?;
+        out.write_all(b"\n")
+    }
+
+    /// splits the first response off `recording`, returning it with whatever follows it
+    pub fn parse(recording: &'a [u8]) -> Result<(Self, &'a [u8])> {
+        let (header, rest) = recording
+            .strip_prefix(b"@")
+            .ok_or("expected @ at the start of a response")?
+            .pipe(|recording| {
+                recording
+                    .iter()
+                    .position(|byte| *byte == b'\n')
+                    .map(|end| (&recording[..end], &recording[end + 1..]))
+            })
+            .ok_or("response header is not terminated")?;
+        let header = core::str::from_utf8(header).into_wrap_err_dbg("invalid utf8 in response header")?;
+        let (at_millis, body_length, path) = match header.split('\t').collect::<Vec<&str, 4>>().as_slice() {
+            [at_millis, body_length, path] => (
+                parse_field::<u64>(at_millis, "invalid response timestamp")?,
+                parse_field::<usize>(body_length, "invalid body length")?,
+                *path,
+            ),
+            _ => return Err("expected response header with 3 fields"),
+        };
+        let body = rest
+            .get(..body_length)
+            .ok_or("response body is cut short")?;
+        rest[body_length..]
+            .strip_prefix(b"\n")
+            .ok_or("expected newline after the response body")
+            .map(|rest| (Self { at_millis, path, body }, rest))
+    }
+
+    pub fn status<const MAX_TRACK_COUNT: usize>(&self) -> Result<ReaperStatus<MAX_TRACK_COUNT>> {
+        // only a recorded query that asked for TRANSPORT has to answer it
+        let query = match self.commands().any(|command| command == "TRANSPORT") {
+            true => ReaperQuery::new().transport(),
+            false => ReaperQuery::new(),
+        };
+        core::str::from_utf8(self.body)
+            .into_wrap_err_dbg("invalid utf8")
+            .and_then(|body| ReaperStatus::parse_response(body, &query))
+            .wrap_err("parsing recorded response")
+    }
+
+    /// the commands of the recorded request path
+    fn commands(&self) -> impl Iterator<Item = &'a str> {
+        self.path
+            .strip_prefix("/_/")
+            .unwrap_or_default()
+            .split(';')
+    }
+
+    /// the inclusive track range of a recorded `ReaperClient::refresh_folder_depths` query,
+    /// `None` for every other response
+    pub fn folder_depth_range(&self) -> Option<(u16, u16)> {
+        let mut commands = self.commands();
+        let (first, last) = commands
+            .next()?
+            .strip_prefix("TRACK/")?
+            .split_once('-')?;
+        commands
+            .any(|command| command.ends_with("/I_FOLDERDEPTH"))
+            .then(|| Some((first.parse().ok()?, last.parse().ok()?)))
+            .flatten()
+    }
+}
+
+impl<const MAX_TRACK_COUNT: usize> FolderDepths<MAX_TRACK_COUNT> {
+    /// the status `ReaperClient` returned for a recorded response: folder depth refreshes only update
+    /// the depths and give `None`, every other status gets the depths known at that point filled in
+    pub fn replay(&mut self, response: &RecordedResponse) -> Result<Option<ReaperStatus<MAX_TRACK_COUNT>>> {
+        let mut status = response.status()?;
+        match response.folder_depth_range() {
+            Some((first, last)) => {
+                self.update(first, last, &status);
+                Ok(None)
+            }
+            None => {
+                self.apply(&mut status);
+                Ok(Some(status))
+            }
+        }
+    }
+}
+
+/// Iterates the responses of a recording, stops after the first malformed one.
+#[derive(Debug, Clone)]
+pub struct RecordedResponses<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> RecordedResponses<'a> {
+    pub fn new(recording: &'a [u8]) -> Self {
+        Self { rest: recording }
+    }
+}
+
+impl<'a> Iterator for RecordedResponses<'a> {
+    type Item = Result<RecordedResponse<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self
+            .rest
+            .iter()
+            .position(|byte| !matches!(byte, b'\r' | b'\n'))
+            .unwrap_or(self.rest.len());
+        match &self.rest[start..] {
+            [] => None,
+            recording => Some(match RecordedResponse::parse(recording) {
+                Ok((response, rest)) => {
+                    self.rest = rest;
+                    Ok(response)
+                }
+                Err(error) => {
+                    self.rest = &[];
+                    Err(error)
+                }
+            }),
+        }
+    }
+}
+
+/// Hands out recorded responses once they are due, `speed` scales the recorded timing (2.0 plays twice as fast).
+#[derive(Debug, Clone)]
+pub struct Replay<'a> {
+    responses: core::iter::Peekable<RecordedResponses<'a>>,
+    speed: f64,
+}
+
+impl<'a> Replay<'a> {
+    pub fn new(recording: &'a [u8], speed: f64) -> Self {
+        Self {
+            responses: RecordedResponses::new(recording).peekable(),
+            speed,
+        }
+    }
+
+    /// replay time of the next response, a malformed response is due right away
+    pub fn next_at(&mut self) -> Option<u64> {
+        let speed = self.speed;
+        self.responses.peek().map(|response| match response {
+            Ok(response) if speed > 0. => (response.at_millis as f64 / speed) as u64,
+            _ => 0,
+        })
+    }
+
+    /// the next response, if it is due `now` milliseconds after the replay started
+    pub fn next_due(&mut self, now: u64) -> Option<Result<RecordedResponse<'a>>> {
+        self.next_at()
+            .filter(|at| *at <= now)
+            .and_then(|_| self.responses.next())
+    }
+
+    pub fn is_finished(&mut self) -> bool {
+        self.responses.peek().is_none()
+    }
+}
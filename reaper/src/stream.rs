@@ -0,0 +1,273 @@
+use crate::{
+    folders::{parse_folder_depth, write_folder_depth, TRACK_PARAMETER_PREFIX},
+    parse_field, trim_line, truncated, BeatPosition, Marker, ReaperQuery, ReaperStatus, Region, TrackData, TransportData,
+};
+use embedded_wrap_err::{IntoWrapErrDebugExt, Result, WrapErrorExt as _};
+use heapless::{String, Vec};
+
+pub const MAX_COMMAND_SIZE: usize = 16;
+
+/// Leading token of a response line, kept around for reporting lines we don't understand.
+pub type Command = String<MAX_COMMAND_SIZE>;
+
+/// A single line of a web interface response.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Record {
+    Transport(TransportData),
+    BeatPosition(BeatPosition),
+    Track(TrackData),
+    TrackCount(u16),
+    /// `GET/TRACK/tracknumber/I_FOLDERDEPTH`
+    FolderDepth { track_number: u16, depth: i8 },
+    Marker(Marker),
+    Region(Region),
+    /// `MARKER_LIST`, `REGION_LIST` and their `_END` counterparts, which carry no data
+    ListBoundary(Command),
+    /// any command this crate doesn't decode (yet), skipped instead of failing the whole response;
+    /// only the command is kept, not its fields
+    Unknown(Command),
+}
+
+impl Record {
+    /// dispatches on the leading token of the line
+    pub fn parse(line: &str) -> Result<Self> {
+        let line = trim_line(line);
+        match line.split('\t').next().unwrap_or_default().trim() {
+            "TRANSPORT" => TransportData::parse(line)
+                .wrap_err("parsing TRANSPORT")
+                .map(Self::Transport),
+            "BEATPOS" => BeatPosition::parse(line)
+                .wrap_err("parsing BEATPOS")
+                .map(Self::BeatPosition),
+            "TRACK" => TrackData::parse(line)
+                .wrap_err("parsing TRACK")
+                .map(Self::Track),
+            "NTRACK" => match line.split('\t').collect::<Vec<&str, 4>>().as_slice() {
+                [_ntrack, count] => parse_field(count, "invalid NTRACK count").map(Self::TrackCount),
+                _ => Err("expected NTRACK with 1 field"),
+            },
+            "MARKER" => Marker::parse(line)
+                .wrap_err("parsing MARKER")
+                .map(Self::Marker),
+            "REGION" => Region::parse(line)
+                .wrap_err("parsing REGION")
+                .map(Self::Region),
+            boundary @ ("MARKER_LIST" | "MARKER_LIST_END" | "REGION_LIST" | "REGION_LIST_END") => Ok(Self::ListBoundary(truncated(boundary))),
+            parameter if parameter.starts_with(TRACK_PARAMETER_PREFIX) => match parse_folder_depth(line) {
+                Some(folder_depth) => folder_depth
+                    .wrap_err("parsing I_FOLDERDEPTH")
+                    .map(|(track_number, depth)| Self::FolderDepth { track_number, depth }),
+                None => Ok(Self::Unknown(truncated(parameter))),
+            },
+            unknown => Ok(Self::Unknown(truncated(unknown))),
+        }
+    }
+
+    /// writes the line in the exact format [`Record::parse`] expects, without the trailing newline.
+    /// [`Record::Unknown`] only writes its command, as that is all that was kept of the line
+    pub fn write(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        match self {
+            Self::Transport(transport) => transport.write(out),
+            Self::BeatPosition(beat_position) => beat_position.write(out),
+            Self::Track(track) => track.write(out),
+            Self::TrackCount(count) => write!(out, "NTRACK\t{count}"),
+            Self::FolderDepth { track_number, depth } => write_folder_depth(out, *track_number, *depth),
+            Self::Marker(marker) => marker.write(out),
+            Self::Region(region) => region.write(out),
+            Self::ListBoundary(command) | Self::Unknown(command) => out.write_str(command),
+        }
+    }
+}
+
+/// Incremental parser fed with body chunks straight off the socket.
+///
+/// Only a single line is ever buffered, so the size of the whole response is
+/// bounded by nothing but the number of tracks the caller decides to keep.
+pub struct ResponseParser<const MAX_LINE_SIZE: usize> {
+    line: Vec<u8, MAX_LINE_SIZE>,
+    overflowed: bool,
+}
+
+impl<const MAX_LINE_SIZE: usize> Default for ResponseParser<MAX_LINE_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const MAX_LINE_SIZE: usize> ResponseParser<MAX_LINE_SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            line: Vec::new(),
+            overflowed: false,
+        }
+    }
+
+    /// feeds the next chunk, calling `on_record` for every line it completes
+    pub fn feed(&mut self, chunk: &[u8], mut on_record: impl FnMut(Result<Record>) -> Result<()>) -> Result<()> {
+        chunk.iter().try_for_each(|byte| match byte {
+            b'\n' => self.complete_line(&mut on_record),
+            byte => {
+                if self.line.push(*byte).is_err() {
+                    self.overflowed = true;
+                }
+                Ok(())
+            }
+        })
+    }
+
+    /// flushes the last line if the body did not end with a newline
+    pub fn finish(&mut self, mut on_record: impl FnMut(Result<Record>) -> Result<()>) -> Result<()> {
+        self.complete_line(&mut on_record)
+    }
+
+    fn complete_line(&mut self, on_record: &mut impl FnMut(Result<Record>) -> Result<()>) -> Result<()> {
+        let overflowed = core::mem::take(&mut self.overflowed);
+        let result = match (overflowed, core::str::from_utf8(&self.line).map(trim_line)) {
+            (true, _) => Some(Err("line too long")),
+            (false, Ok("")) => None,
+            (false, Ok(line)) => Some(Record::parse(line)),
+            (false, Err(error)) => Some(Err::<Record, _>(error).into_wrap_err_dbg("invalid utf8")),
+        };
+        self.line.clear();
+        result.map(on_record).unwrap_or(Ok(()))
+    }
+}
+
+/// Collects records into a [`ReaperStatus`], dropping tracks that don't fit.
+///
+/// Records may arrive in any order, so several commands can share one request.
+/// Markers and regions are left to [`crate::MarkerList`].
+pub struct StatusBuilder<const MAX_TRACK_COUNT: usize> {
+    status: ReaperStatus<MAX_TRACK_COUNT>,
+    /// folder depths of tracks whose `TRACK` line didn't arrive yet
+    pending_folder_depths: Vec<(u16, i8), MAX_TRACK_COUNT>,
+    has_transport: bool,
+    records: usize,
+    skipped: usize,
+}
+
+impl<const MAX_TRACK_COUNT: usize> Default for StatusBuilder<MAX_TRACK_COUNT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const MAX_TRACK_COUNT: usize> StatusBuilder<MAX_TRACK_COUNT> {
+    pub fn new() -> Self {
+        Self {
+            status: ReaperStatus {
+                transport: Default::default(),
+                beat_position: None,
+                track_count: None,
+                tracks: Vec::new(),
+            },
+            pending_folder_depths: Vec::new(),
+            has_transport: false,
+            records: 0,
+            skipped: 0,
+        }
+    }
+
+    pub fn push(&mut self, record: Record) {
+        self.records += 1;
+        match record {
+            Record::Transport(transport) => {
+                self.status.transport = transport;
+                self.has_transport = true;
+            }
+            Record::BeatPosition(beat_position) => self.status.beat_position = Some(beat_position),
+            Record::TrackCount(count) => self.status.track_count = Some(count),
+            Record::Track(track) => {
+                self.status.tracks.push(track).ok();
+            }
+            Record::FolderDepth { track_number, depth } => {
+                if !self.set_folder_depth(track_number, depth) {
+                    self.pending_folder_depths
+                        .push((track_number, depth))
+                        .ok();
+                }
+            }
+            Record::Marker(_) | Record::Region(_) | Record::ListBoundary(_) => {}
+            Record::Unknown(_command) => {
+                self.skipped += 1;
+                #[cfg(not(feature = "std"))]
+                defmt::warn!("skipping unknown command: {}", _command.as_str());
+            }
+        }
+    }
+
+    /// number of lines skipped because their command is not understood
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// sets the depth of the most recent track with that number, which is the only one in a real response
+    fn set_folder_depth(&mut self, track_number: u16, depth: i8) -> bool {
+        self.status
+            .tracks
+            .iter_mut()
+            .rev()
+            .find(|track| track.track_number == track_number)
+            .map(|track| track.folder_depth = Some(depth))
+            .is_some()
+    }
+
+    /// fails if the response to `query` is empty or misses the `TRANSPORT` line it asked for,
+    /// which would otherwise show up as a project stopped at 0
+    pub fn finish(mut self, query: &ReaperQuery<'_>) -> Result<ReaperStatus<MAX_TRACK_COUNT>> {
+        core::mem::take(&mut self.pending_folder_depths)
+            .into_iter()
+            .for_each(|(track_number, depth)| {
+                self.set_folder_depth(track_number, depth);
+            });
+        match (self.records, self.has_transport) {
+            (0, _) => Err("empty response"),
+            (_, false) if query.expects("TRANSPORT") => Err("response has no TRANSPORT line"),
+            _ => Ok(self.status),
+        }
+    }
+}
+
+impl<const MAX_TRACK_COUNT: usize> ReaperStatus<MAX_TRACK_COUNT> {
+    /// parses a full status that is already in memory, like the one [`ReaperStatus::write`] produces
+    pub fn parse(response: &str) -> Result<Self> {
+        Self::parse_response(response, &ReaperQuery::new().transport())
+    }
+
+    /// parses the response to `query` that is already in memory
+    pub fn parse_response(response: &str, query: &ReaperQuery<'_>) -> Result<Self> {
+        response
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .try_fold(StatusBuilder::new(), |mut builder, line| {
+                Record::parse(line).map(|record| builder.push(record)).map(|_| builder)
+            })
+            .wrap_err("extracting track data")
+            .and_then(|builder| builder.finish(query))
+    }
+
+    /// writes the status back in the tab-separated format the web interface produces,
+    /// so that `ReaperStatus::parse` of the output gives back an equal status
+    pub fn write(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        self.transport.write(out)?;
+        out.write_char('\n')?;
+        if let Some(beat_position) = &self.beat_position {
+            beat_position.write(out)?;
+            out.write_char('\n')?;
+        }
+        if let Some(count) = self.track_count {
+            writeln!(out, "NTRACK\t{count}")?;
+        }
+        // each folder depth goes right after its track, so tracks sharing a number keep their own depth
+        self.tracks.iter().try_for_each(|track| {
+            track.write(out)?;
+            out.write_char('\n')?;
+            match track.folder_depth {
+                Some(depth) => write_folder_depth(out, track.track_number, depth).and_then(|_| out.write_char('\n')),
+                None => Ok(()),
+            }
+        })
+    }
+}
@@ -0,0 +1,14 @@
+use reaper::BeatPosition;
+
+#[test]
+fn average_tempo_since_the_project_start() {
+    let at = |position_seconds, full_beat_position| BeatPosition {
+        position_seconds,
+        full_beat_position,
+        ..Default::default()
+    };
+    assert_eq!(at(12., 24.).average_tempo(), Some(120.));
+    assert_eq!(at(0., 0.).average_tempo(), None);
+    // the pre-roll before the project start
+    assert_eq!(at(-2., -4.).average_tempo(), None);
+}
@@ -0,0 +1,27 @@
+use reaper::{ColorByteOrder, TrackColor};
+
+/// a custom color with `0x11` in the low, `0x22` in the middle and `0x33` in the high byte
+const CUSTOM: TrackColor = TrackColor(TrackColor::CUSTOM_COLOR_FLAG | 0x33_22_11);
+
+#[test]
+fn byte_order_decides_which_end_is_red() {
+    assert_eq!(CUSTOM.rgb(ColorByteOrder::Bgr), Some((0x11, 0x22, 0x33)));
+    assert_eq!(CUSTOM.rgb(ColorByteOrder::Rgb), Some((0x33, 0x22, 0x11)));
+}
+
+#[test]
+fn colors_without_the_custom_flag_are_the_theme_default() {
+    assert_eq!(TrackColor::NONE.rgb(ColorByteOrder::Bgr), None);
+    assert_eq!(TrackColor(0x33_22_11).rgb(ColorByteOrder::Bgr), None);
+    assert_eq!(TrackColor(0x33_22_11).rgb(ColorByteOrder::Rgb), None);
+    // black is still a custom color
+    assert_eq!(TrackColor(TrackColor::CUSTOM_COLOR_FLAG).rgb(ColorByteOrder::Bgr), Some((0, 0, 0)));
+}
+
+#[test]
+fn from_rgb_inverts_rgb() {
+    [ColorByteOrder::Bgr, ColorByteOrder::Rgb].into_iter().for_each(|order| {
+        assert_eq!(TrackColor::from_rgb((0x11, 0x22, 0x33), order).rgb(order), Some((0x11, 0x22, 0x33)));
+    });
+    assert_eq!(TrackColor::from_rgb((0x11, 0x22, 0x33), ColorByteOrder::Bgr), CUSTOM);
+}
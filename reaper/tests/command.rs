@@ -0,0 +1,32 @@
+use reaper::{ActionId, LinearGain, ReaperCommand, Switch};
+
+fn path(command: ReaperCommand) -> String {
+    command.to_path::<128>().expect("fits").to_string()
+}
+
+#[test]
+fn track_toggles() {
+    assert_eq!(path(ReaperCommand::Mute { track: 3, state: Switch::Toggle }), "/_/SET/TRACK/3/MUTE/-1");
+    assert_eq!(path(ReaperCommand::Mute { track: 3, state: Switch::Off }), "/_/SET/TRACK/3/MUTE/0");
+    assert_eq!(path(ReaperCommand::Solo { track: 0, state: Switch::On }), "/_/SET/TRACK/0/SOLO/1");
+    assert_eq!(path(ReaperCommand::RecordArm { track: 12, state: Switch::Toggle }), "/_/SET/TRACK/12/RECARM/-1");
+    assert_eq!(path(ReaperCommand::Select { track: 1, state: Switch::On }), "/_/SET/TRACK/1/SEL/1");
+}
+
+#[test]
+fn volume_pan_and_position() {
+    assert_eq!(path(ReaperCommand::Volume { track: 2, volume: LinearGain(0.5) }), "/_/SET/TRACK/2/VOL/0.500000");
+    assert_eq!(path(ReaperCommand::Pan { track: 2, pan: -1. }), "/_/SET/TRACK/2/PAN/-1.000000");
+    assert_eq!(path(ReaperCommand::SetPosition { position_seconds: 12.345 }), "/_/SET/POS/12.345000");
+}
+
+#[test]
+fn actions() {
+    assert_eq!(path(ReaperCommand::Play), "/_/1007");
+    assert_eq!(path(ReaperCommand::Stop), "/_/1016");
+    assert_eq!(path(ReaperCommand::Record), "/_/1013");
+    assert_eq!(path(ReaperCommand::Pause), "/_/1008");
+    assert_eq!(path(ReaperCommand::Action(ActionId::Numeric(40044))), "/_/40044");
+    // a named action can't smuggle in a second command
+    assert_eq!(path(ReaperCommand::Action(ActionId::Named("_SWS;1016/x y"))), "/_/_SWS%3B1016%2Fx%20y");
+}
@@ -0,0 +1,130 @@
+use reaper::{Decibel, PlayState, RawTrackFlags, ReaperStatus, StatusChange, TrackData, TrackFlags, TransportData};
+
+fn track(track_number: u16, flags: u16) -> TrackData {
+    TrackData {
+        track_number,
+        flags: RawTrackFlags(flags),
+        last_meter_peak: Decibel(-1500),
+        last_meter_pos: Decibel(-1500),
+        ..Default::default()
+    }
+}
+
+fn status(play_state: PlayState, tracks: impl IntoIterator<Item = TrackData>) -> ReaperStatus<8> {
+    ReaperStatus {
+        transport: TransportData {
+            play_state,
+            ..Default::default()
+        },
+        beat_position: None,
+        track_count: None,
+        tracks: tracks.into_iter().collect(),
+    }
+}
+
+fn changes(current: &ReaperStatus<8>, previous: &ReaperStatus<8>) -> Vec<StatusChange> {
+    current.diff(previous).collect()
+}
+
+#[test]
+fn identical_statuses_have_no_changes() {
+    let status = status(PlayState::Playing, [track(1, 0), track(2, 0)]);
+    assert_eq!(changes(&status, &status.clone()), []);
+}
+
+#[test]
+fn transport_changes() {
+    let previous = status(PlayState::Stopped, []);
+    let mut current = status(PlayState::Recording, []);
+    current.transport.repeat_on = true;
+    assert_eq!(
+        changes(&current, &previous),
+        [
+            StatusChange::PlayState {
+                previous: PlayState::Stopped,
+                current: PlayState::Recording,
+            },
+            StatusChange::Repeat { repeat_on: true },
+        ]
+    );
+}
+
+#[test]
+fn flags_are_split_into_added_and_removed() {
+    let armed = TrackFlags::RecordArmed as u16;
+    let muted = TrackFlags::Muted as u16;
+    let previous = status(PlayState::Stopped, [track(1, muted)]);
+    let current = status(PlayState::Stopped, [track(1, armed)]);
+    let changes = changes(&current, &previous);
+
+    assert_eq!(
+        changes,
+        [StatusChange::TrackFlags {
+            track_number: 1,
+            added: RawTrackFlags(armed),
+            removed: RawTrackFlags(muted),
+        }]
+    );
+    assert_eq!(changes[0].track_flag_added(TrackFlags::RecordArmed), Some(1));
+    assert_eq!(changes[0].track_flag_removed(TrackFlags::Muted), Some(1));
+    assert_eq!(changes[0].track_flag_added(TrackFlags::Muted), None);
+}
+
+#[test]
+fn clips_are_only_reported_when_they_start() {
+    let clipping = TrackData {
+        last_meter_peak: Decibel(3),
+        ..track(1, 0)
+    };
+    let quiet = status(PlayState::Playing, [track(1, 0)]);
+    let loud = status(PlayState::Playing, [clipping]);
+
+    assert_eq!(changes(&loud, &quiet), [StatusChange::ClipStarted { track_number: 1 }]);
+    assert_eq!(changes(&loud, &loud.clone()), []);
+    assert_eq!(changes(&quiet, &loud), []);
+}
+
+#[test]
+fn reordered_tracks_are_matched_by_number() {
+    let soloed = TrackFlags::Soloed as u16;
+    let previous = status(PlayState::Stopped, [track(1, 0), track(2, 0), track(3, 0)]);
+    let current = status(PlayState::Stopped, [track(3, 0), track(1, soloed), track(2, 0)]);
+    assert_eq!(
+        changes(&current, &previous),
+        [StatusChange::TrackFlags {
+            track_number: 1,
+            added: RawTrackFlags(soloed),
+            removed: RawTrackFlags(0),
+        }]
+    );
+}
+
+#[test]
+fn added_and_removed_tracks_are_reported() {
+    let previous = status(PlayState::Stopped, [track(1, 0), track(2, 0), track(3, 0)]);
+    let removed = status(PlayState::Stopped, [track(1, 0), track(2, 0)]);
+    let added = status(PlayState::Stopped, [track(1, 0), track(2, 0), track(3, 0), track(4, 0)]);
+
+    assert_eq!(
+        changes(&removed, &previous),
+        [
+            StatusChange::TrackCount { previous: 3, current: 2 },
+            StatusChange::TrackRemoved { track_number: 3 },
+        ]
+    );
+    assert_eq!(
+        changes(&added, &previous),
+        [
+            StatusChange::TrackCount { previous: 3, current: 4 },
+            StatusChange::TrackAdded { track_number: 4 },
+        ]
+    );
+}
+
+#[test]
+fn track_count_prefers_ntrack() {
+    let previous = status(PlayState::Stopped, [track(1, 0)]);
+    let mut current = previous.clone();
+    current.track_count = Some(100);
+    assert_eq!(changes(&current, &previous), [StatusChange::TrackCount { previous: 1, current: 100 }]);
+}
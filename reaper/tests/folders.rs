@@ -0,0 +1,85 @@
+use reaper::{FolderDepths, ReaperQuery, ReaperStatus};
+
+/// Drums { Kick, Snare, Toms { Tom 1, Tom 2 } }, Bass
+const RESPONSE: &str = "\
+TRANSPORT\t0\t0.000000\t0\t0:00.000\t1.1.00
+TRACK\t1\tDrums\t1\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t1\t0
+TRACK\t2\tKick\t0\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t0\t0
+TRACK\t3\tSnare\t0\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t0\t0
+TRACK\t4\tToms\t1\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t0\t0
+TRACK\t5\tTom 1\t0\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t0\t0
+TRACK\t6\tTom 2\t0\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t0\t0
+TRACK\t7\tBass\t0\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t1\t0
+GET/TRACK/1/I_FOLDERDEPTH\t1
+GET/TRACK/2/I_FOLDERDEPTH\t0
+GET/TRACK/3/I_FOLDERDEPTH\t0
+GET/TRACK/4/I_FOLDERDEPTH\t1.000000
+GET/TRACK/5/I_FOLDERDEPTH\t0
+GET/TRACK/6/I_FOLDERDEPTH\t-2
+GET/TRACK/7/I_FOLDERDEPTH\t0
+";
+
+#[test]
+fn folder_depths_build_a_tree() {
+    let status = ReaperStatus::<8>::parse(RESPONSE).expect("valid response");
+    let tree = status.folder_tree();
+    let children = |track_number| tree.children(track_number).map(|node| node.track_number).collect::<Vec<_>>();
+    let roots = tree.roots().map(|node| node.track_number).collect::<Vec<_>>();
+
+    assert_eq!(roots, [1, 7]);
+    assert_eq!(children(1), [2, 3, 4]);
+    assert_eq!(children(4), [5, 6]);
+    assert_eq!(tree.node(6).map(|node| node.level), Some(2));
+    assert_eq!(tree.deepest_level_fitting(7), Some(2));
+    assert_eq!(tree.deepest_level_fitting(5), Some(1));
+    assert_eq!(tree.deepest_level_fitting(1), None);
+}
+
+#[test]
+fn every_track_of_a_folder_shares_its_bus() {
+    let status = ReaperStatus::<8>::parse(RESPONSE).expect("valid response");
+    let tree = status.folder_tree();
+    let buses = (0..status.tracks.len() + 1)
+        .map(|index| tree.bus(index).map(|node| node.track_number))
+        .collect::<Vec<_>>();
+    assert_eq!(buses, [Some(1), Some(1), Some(1), Some(1), Some(1), Some(1), None, None]);
+}
+
+#[test]
+fn folder_depths_fill_in_later_statuses() {
+    let mut depths = FolderDepths::<8>::new();
+    let refresh = ReaperStatus::<8>::parse(RESPONSE).expect("valid response");
+    // asked for more tracks than the project has
+    assert!(!depths.update(1, 9, &refresh));
+
+    let without_depths = RESPONSE
+        .lines()
+        .filter(|line| !line.starts_with("GET/"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut status = ReaperStatus::<8>::parse(&without_depths).expect("valid response");
+    depths.apply(&mut status);
+    assert_eq!(status.folder_tree(), refresh.folder_tree());
+
+    // the project shrank to the drums folder alone
+    let shrunk = RESPONSE
+        .lines()
+        .filter(|line| line.contains("\tDrums\t") || line.starts_with("GET/TRACK/1/"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let refresh_query = ReaperQuery::new().track_range(1, 7).folder_depths(1, 7);
+    let shrunk = ReaperStatus::<8>::parse_response(&shrunk, &refresh_query).expect("valid response");
+    assert!(!depths.update(1, 7, &shrunk));
+    assert_eq!(depths.get(1), Some(1));
+    assert_eq!(depths.get(4), None);
+}
+
+#[test]
+fn folder_depth_query_path() {
+    let path = ReaperQuery::new()
+        .track_range(1, 2)
+        .folder_depths(1, 2)
+        .to_path::<128>()
+        .expect("fits");
+    assert_eq!(path.as_str(), "/_/TRACK/1-2;GET/TRACK/1/I_FOLDERDEPTH;GET/TRACK/2/I_FOLDERDEPTH");
+}
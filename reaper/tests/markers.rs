@@ -0,0 +1,80 @@
+use reaper::{Marker, MarkerList, Record, Region, MAX_MARKER_COUNT, MAX_REGION_COUNT};
+
+fn marker(id: u32, position_seconds: f64) -> Marker {
+    Marker {
+        id,
+        position_seconds,
+        ..Default::default()
+    }
+}
+
+fn region(id: u32, start_seconds: f64, end_seconds: f64) -> Region {
+    Region {
+        id,
+        start_seconds,
+        end_seconds,
+        ..Default::default()
+    }
+}
+
+fn list(markers: &[Marker], regions: &[Region]) -> MarkerList {
+    let mut list = MarkerList::new();
+    markers
+        .iter()
+        .cloned()
+        .map(Record::Marker)
+        .chain(regions.iter().cloned().map(Record::Region))
+        .try_for_each(|record| list.push(record))
+        .expect("fits");
+    list
+}
+
+#[test]
+fn current_region_includes_its_start_but_not_its_end() {
+    let list = list(&[], &[region(1, 10., 20.), region(2, 20., 30.)]);
+    let current = |position_seconds| list.current_region(position_seconds).map(|region| region.id);
+
+    assert_eq!(current(9.999), None);
+    assert_eq!(current(10.), Some(1));
+    assert_eq!(current(19.999), Some(1));
+    assert_eq!(current(20.), Some(2));
+    assert_eq!(current(30.), None);
+}
+
+#[test]
+fn current_region_prefers_the_one_that_started_last() {
+    // the outer region comes last on purpose, the order Reaper sends doesn't matter
+    let list = list(&[], &[region(2, 10., 20.), region(1, 0., 60.)]);
+    let current = |position_seconds| list.current_region(position_seconds).map(|region| region.id);
+
+    assert_eq!(current(5.), Some(1));
+    assert_eq!(current(15.), Some(2));
+    assert_eq!(current(20.), Some(1));
+}
+
+#[test]
+fn next_marker_is_strictly_after_the_playhead() {
+    let list = list(&[marker(2, 30.), marker(1, 10.), marker(3, 30.5)], &[]);
+    let next = |position_seconds| list.next_marker(position_seconds).map(|marker| marker.id);
+
+    assert_eq!(next(0.), Some(1));
+    assert_eq!(next(10.), Some(2));
+    assert_eq!(next(30.), Some(3));
+    assert_eq!(next(30.5), None);
+    assert_eq!(MarkerList::new().next_marker(0.), None);
+}
+
+#[test]
+fn overflowing_lists_are_reported() {
+    let mut list = MarkerList::new();
+    (0..MAX_MARKER_COUNT as u32).for_each(|id| list.push(Record::Marker(marker(id, id as f64))).expect("fits"));
+    (0..MAX_REGION_COUNT as u32).for_each(|id| list.push(Record::Region(region(id, 0., 1.))).expect("fits"));
+
+    assert_eq!(list.push(Record::Marker(marker(99, 0.))), Err("too many markers"));
+    assert_eq!(list.push(Record::Region(region(99, 0., 1.))), Err("too many regions"));
+    assert_eq!(list.markers.len(), MAX_MARKER_COUNT);
+    assert_eq!(list.next_marker(0.5).map(|marker| marker.id), Some(1));
+
+    let response = (0..=MAX_MARKER_COUNT).fold(String::from("MARKER_LIST\n"), |response, id| response + &format!("MARKER\tm\t{id}\t{id}.000000\t0\n"));
+    assert!(MarkerList::parse(&response).is_err());
+}
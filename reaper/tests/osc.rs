@@ -0,0 +1,132 @@
+use reaper::{for_each_osc_message, Decibel, OscAccumulator, OscArgument, OscMessage, PlayState, TrackFlags};
+
+fn pad(out: &mut Vec<u8>) {
+    while !out.len().is_multiple_of(4) {
+        out.push(0);
+    }
+}
+
+fn string(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(value.as_bytes());
+    out.push(0);
+    pad(out);
+}
+
+fn message(address: &str, type_tags: &str, arguments: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    string(&mut out, address);
+    string(&mut out, &format!(",{type_tags}"));
+    out.extend_from_slice(arguments);
+    out
+}
+
+fn float(address: &str, value: f32) -> Vec<u8> {
+    message(address, "f", &value.to_be_bytes())
+}
+
+fn bundle(messages: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = b"#bundle\0".to_vec();
+    out.extend_from_slice(&1u64.to_be_bytes());
+    messages.iter().for_each(|message| {
+        out.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        out.extend_from_slice(message);
+    });
+    out
+}
+
+#[test]
+fn decodes_every_supported_argument_type() {
+    let mut arguments = Vec::new();
+    arguments.extend_from_slice(&(-3i32).to_be_bytes());
+    arguments.extend_from_slice(&0.5f32.to_be_bytes());
+    string(&mut arguments, "Kick");
+    arguments.extend_from_slice(&3u32.to_be_bytes());
+    arguments.extend_from_slice(&[1, 2, 3, 0]);
+    let packet = message("/test", "ifsbTN", &arguments);
+
+    let message = OscMessage::parse(&packet).expect("valid message");
+    let arguments = message.arguments().collect::<Result<Vec<_>, _>>().expect("valid arguments");
+    assert_eq!(message.address, "/test");
+    assert_eq!(
+        arguments,
+        [
+            OscArgument::Int(-3),
+            OscArgument::Float(0.5),
+            OscArgument::String("Kick"),
+            OscArgument::Blob(&[1, 2, 3]),
+            OscArgument::True,
+            OscArgument::Nil,
+        ]
+    );
+}
+
+#[test]
+fn truncated_messages_are_errors() {
+    let packet = float("/time", 1.);
+    assert!(OscMessage::parse(&packet[..3]).is_err());
+    let message = OscMessage::parse(&packet[..packet.len() - 2]).expect("address and type tags are complete");
+    assert!(message.first_argument().is_err());
+}
+
+#[test]
+fn deeply_nested_bundles_are_errors() {
+    let nested = |depth| (0..depth).fold(float("/time", 1.), |packet, _| bundle(&[packet]));
+    let count = |packet: &[u8]| {
+        let mut count = 0;
+        for_each_osc_message(packet, &mut |_| {
+            count += 1;
+            Ok(())
+        })
+        .map(|_| count)
+    };
+    assert_eq!(count(&nested(4)), Ok(1));
+    assert!(count(&nested(5)).is_err());
+}
+
+#[test]
+fn default_patterns_map_onto_the_status() {
+    let mut osc = OscAccumulator::<8>::new();
+    osc.feed(&bundle(&[
+        float("/record", 1.),
+        float("/play", 1.),
+        float("/time", 12.5),
+        float("/track/2/vu", 1.),
+        float("/track/1/vu", 0.),
+        float("/track/2/mute", 1.),
+        message("/track/2/name", "s", b"Bass\0\0\0\0"),
+        float("/track/2/volume/db", -6.),
+        float("/master/vu", 0.5),
+        float("/some/unknown/address", 1.),
+    ]))
+    .expect("valid bundle");
+
+    let status = osc.status();
+    assert_eq!(status.transport.play_state, PlayState::Recording);
+    assert_eq!(status.transport.position_seconds, 12.5);
+    assert_eq!(status.tracks.iter().map(|track| track.track_number).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(status.tracks[0].last_meter_pos, Decibel(-270));
+    assert_eq!(status.tracks[1].last_meter_pos, Decibel::SILENCE);
+    assert_eq!(status.tracks[2].last_meter_pos, osc.vu_ceiling);
+    assert!(status.tracks[2].flags.contains(TrackFlags::Muted));
+    assert_eq!(status.tracks[2].name.as_str(), "Bass");
+    assert_eq!(status.tracks[2].volume.to_decibel(), Decibel(-60));
+    assert_eq!(osc.skipped(), 1);
+
+    osc.feed(&float("/stop", 1.)).expect("valid message");
+    assert_eq!(osc.status().transport.play_state, PlayState::Stopped);
+}
+
+#[test]
+fn unknown_track_parameters_dont_create_tracks() {
+    let mut osc = OscAccumulator::<8>::new();
+    osc.feed(&float("/track/1/vu", 1.)).expect("valid message");
+    let tracks = osc.status().tracks.clone();
+
+    osc.feed(&message("/track/5/fx/1/name", "s", b"ReaEQ\0\0\0")).expect("valid message");
+    assert_eq!(osc.status().tracks, tracks);
+    assert_eq!(osc.skipped(), 1);
+
+    // neither do known ones with the wrong argument
+    assert!(osc.feed(&message("/track/6/name", "f", &1f32.to_be_bytes())).is_err());
+    assert_eq!(osc.status().tracks, tracks);
+}
@@ -0,0 +1,75 @@
+use reaper::{ActionId, ReaperQuery, MAX_QUERY_COMMANDS};
+
+fn path(query: &ReaperQuery) -> String {
+    query.to_path::<256>().expect("fits").to_string()
+}
+
+#[test]
+fn commands_are_joined_with_semicolons() {
+    let query = ReaperQuery::new()
+        .transport()
+        .beat_position()
+        .track_count()
+        .track(0)
+        .track_range(1, 63)
+        .marker_list()
+        .region_list();
+    assert_eq!(path(&query), "/_/TRANSPORT;BEATPOS;NTRACK;TRACK/0;TRACK/1-63;MARKER_LIST;REGION_LIST");
+}
+
+#[test]
+fn user_supplied_names_are_percent_escaped() {
+    let query = ReaperQuery::new()
+        .ext_state("my section", "a;b/c")
+        .command_state(ActionId::Named("_SWS_ABOUT"))
+        .command_state(ActionId::Named("ąę~.-"));
+    assert_eq!(path(&query), "/_/GET/EXTSTATE/my%20section/a%3Bb%2Fc;GET/_SWS_ABOUT;GET/%C4%85%C4%99~.-");
+}
+
+#[test]
+fn too_many_commands_fail_instead_of_being_dropped() {
+    let full = (0..MAX_QUERY_COMMANDS as u16).fold(ReaperQuery::new(), ReaperQuery::track);
+    assert_eq!(full.commands().len(), MAX_QUERY_COMMANDS);
+    assert!(full.to_path::<256>().is_ok());
+
+    let overflowed = full.transport();
+    assert_eq!(overflowed.commands().len(), MAX_QUERY_COMMANDS);
+    assert_eq!(overflowed.to_path::<256>(), Err("too many commands in query"));
+}
+
+#[test]
+fn paths_that_dont_fit_are_errors() {
+    assert!(ReaperQuery::new().transport().to_path::<8>().is_err());
+}
+
+#[test]
+fn expects_matches_whole_commands_and_parameter_paths() {
+    let query = ReaperQuery::new()
+        .transport()
+        .folder_depth(3)
+        .marker_list();
+    assert!(query.expects("TRANSPORT"));
+    assert!(query.expects("GET/TRACK/3/I_FOLDERDEPTH"));
+    assert!(query.expects("MARKER"));
+    assert!(query.expects("MARKER_LIST_END"));
+    // a prefix of a command is not the command
+    assert!(!query.expects("TRANSPORTX"));
+    assert!(!query.expects("GET/TRACKS"));
+    assert!(!query.expects("TRACK"));
+    assert!(!query.expects("REGION"));
+}
+
+#[test]
+fn track_capacity_counts_every_track_command() {
+    let query = ReaperQuery::new()
+        .transport()
+        .track(0)
+        .track_range(1, 7);
+    assert_eq!(query.max_track_lines(), Some(8));
+    assert!(query.check_track_capacity(8).is_ok());
+    assert!(query.check_track_capacity(7).is_err());
+
+    let all_tracks = ReaperQuery::new().all_tracks();
+    assert_eq!(all_tracks.max_track_lines(), None);
+    assert!(all_tracks.check_track_capacity(usize::MAX).is_err());
+}
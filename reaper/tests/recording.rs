@@ -0,0 +1,129 @@
+use reaper::{FolderDepths, PlayState, RecordedResponse, RecordedResponses, ReaperStatus, Replay};
+
+/// a few seconds of the mock server going from stopped to playing to recording, with a clipping crash track
+/// in a drums folder, the folder depths are fetched once at the start
+const SESSION: &[u8] = include_bytes!("recordings/session.rec");
+
+#[test]
+fn responses_round_trip() {
+    let responses = [
+        RecordedResponse {
+            at_millis: 0,
+            path: "/_/TRANSPORT",
+            body: b"TRANSPORT\t0\t0.000000\t0\t0:00.000\t1.1.00\n",
+        },
+        // a body cut short by the network ends without a newline
+        RecordedResponse {
+            at_millis: 1234,
+            path: "/_/TRANSPORT;TRACK",
+            body: b"TRANSPORT\t1\t1.2",
+        },
+        // recorded byte for byte, even when it isn't valid UTF-8
+        RecordedResponse {
+            at_millis: 1300,
+            path: "/_/TRACK",
+            body: b"TRACK\t1\tKick \xff\n",
+        },
+    ];
+    let mut recording = Vec::new();
+    responses
+        .iter()
+        .for_each(|response| response.write(&mut recording).expect("writing"));
+
+    let parsed = RecordedResponses::new(&recording)
+        .collect::<Result<Vec<_>, _>>()
+        .expect("parsing");
+    assert_eq!(parsed, responses);
+}
+
+#[test]
+fn malformed_recordings_stop_the_iteration() {
+    let mut responses = RecordedResponses::new(b"@0\t100\t/_/TRANSPORT\nTRANSPORT\n@5\t0\t/_/TRANSPORT\n\n");
+    assert!(responses.next().expect("an entry").is_err());
+    assert!(responses.next().is_none());
+}
+
+#[test]
+fn replay_scales_the_recorded_timing() {
+    let mut recording = Vec::new();
+    [0, 100, 300].into_iter().for_each(|at_millis| {
+        RecordedResponse {
+            at_millis,
+            path: "/_/TRANSPORT",
+            body: b"",
+        }
+        .write(&mut recording)
+        .expect("writing");
+    });
+
+    let mut replay = Replay::new(&recording, 2.);
+    assert!(replay.next_due(0).is_some());
+    assert_eq!(replay.next_at(), Some(50));
+    assert!(replay.next_due(49).is_none());
+    assert!(replay.next_due(50).is_some());
+    assert!(replay.next_due(1000).is_some());
+    assert!(replay.is_finished());
+}
+
+#[test]
+fn invalid_utf8_fails_on_replay() {
+    let response = RecordedResponse {
+        at_millis: 0,
+        path: "/_/TRACK",
+        body: b"TRACK\t1\tKick \xff\n",
+    };
+    assert!(response.status::<8>().is_err());
+}
+
+#[test]
+fn folder_depth_refreshes_fill_in_later_statuses() {
+    let refresh = RecordedResponse {
+        at_millis: 0,
+        path: "/_/TRACK/0-2;GET/TRACK/0/I_FOLDERDEPTH;GET/TRACK/1/I_FOLDERDEPTH;GET/TRACK/2/I_FOLDERDEPTH",
+        body: b"TRACK\t0\tMASTER\t0\t1.0\t0.0\t-1500\t-1500\t1.0\t0\t0\t0\t1\t0\n\
+                TRACK\t1\tDrums\t0\t1.0\t0.0\t-1500\t-1500\t1.0\t0\t0\t0\t0\t0\n\
+                GET/TRACK/0/I_FOLDERDEPTH\t0\n\
+                GET/TRACK/1/I_FOLDERDEPTH\t1\n",
+    };
+    let poll = RecordedResponse {
+        at_millis: 50,
+        path: "/_/TRANSPORT;BEATPOS;TRACK/0-7",
+        body: b"TRANSPORT\t0\t0.000000\t0\t0:00.000\t1.1.00\n\
+                TRACK\t1\tDrums\t0\t1.0\t0.0\t-1500\t-1500\t1.0\t0\t0\t0\t0\t0\n",
+    };
+    assert_eq!(refresh.folder_depth_range(), Some((0, 2)));
+    assert_eq!(poll.folder_depth_range(), None);
+
+    let mut folder_depths = FolderDepths::<8>::new();
+    assert_eq!(folder_depths.replay(&refresh).expect("parsing"), None);
+    let status = folder_depths
+        .replay(&poll)
+        .expect("parsing")
+        .expect("a status");
+    assert_eq!(status.tracks[0].folder_depth, Some(1));
+    // the project ended within the refresh, so nothing is known past its last track
+    assert_eq!(folder_depths.get(2), None);
+}
+
+#[test]
+fn recorded_session_parses() {
+    let mut folder_depths = FolderDepths::<64>::new();
+    let play_states = RecordedResponses::new(SESSION)
+        .filter_map(|response| {
+            response
+                .and_then(|response| folder_depths.replay(&response))
+                .expect("every recorded response parses")
+        })
+        .map(|status: ReaperStatus<64>| {
+            assert_eq!(status.tracks.len(), 8);
+            assert_eq!(status.folder_tree().bus(4).map(|bus| bus.track_number), Some(1));
+            status.transport.play_state
+        })
+        .fold(Vec::new(), |mut play_states, play_state| {
+            if play_states.last() != Some(&play_state) {
+                play_states.push(play_state);
+            }
+            play_states
+        });
+    assert_eq!(play_states, [PlayState::Stopped, PlayState::Playing, PlayState::Recording]);
+}
@@ -0,0 +1,17 @@
+use reaper::{ReaperStatus, TrackFlags};
+
+const RESPONSE: &str = "\
+TRANSPORT\t1\t12.345000\t0\t0:12.345\t5.2.00
+NTRACK\t1
+TRACK\t1\tKick\t72\t0.501187\t-0.250000\t-300\t-412\t1.000000\t3\t1\t0\t0\t16777471
+GET/TRACK/1/I_FOLDERDEPTH\t0
+";
+
+#[test]
+fn status_survives_a_json_round_trip() {
+    let status = ReaperStatus::<4>::parse(RESPONSE).expect("valid response");
+    let json = serde_json::to_string(&status).expect("serializable");
+    let parsed: ReaperStatus<4> = serde_json::from_str(&json).expect("deserializable");
+    assert_eq!(parsed, status);
+    assert!(parsed.tracks[0].flags.contains(TrackFlags::RecordArmed));
+}
@@ -0,0 +1,227 @@
+use proptest::prelude::*;
+use reaper::{BeatPosition, Decibel, LinearGain, Marker, MarkerList, PlayState, RawTrackFlags, ReaperQuery, ReaperStatus, Record, Region, TrackColor, TrackData, TrackFlags, TransportData};
+
+const MAX_TRACK_COUNT: usize = 8;
+
+const RECORDED_RESPONSE: &str = "\
+TRANSPORT\t5\t12.345000\t1\t0:12.345\t5.2.00
+BEATPOS\t5\t12.345000\t24.690000\t6\t0.690000\t4\t4
+NTRACK\t2
+TRACK\t0\tMASTER\t0\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t1\t0
+TRACK\t1\tKick\\tIn\t72\t0.501187\t-0.250000\t-300\t-412\t1.000000\t3\t1\t0\t0\t16777471
+TRACK\t2\tBass\t8\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t0\t0
+";
+
+const RECORDED_MARKERS: &str = "\
+MARKER_LIST
+MARKER\tdrop\t2\t30.500000\t0
+MARKER_LIST_END
+REGION_LIST
+REGION\tverse\t1\t0.000000\t20.000000\t16842752
+REGION_LIST_END
+";
+
+fn written(status: &ReaperStatus<MAX_TRACK_COUNT>) -> String {
+    let mut out = String::new();
+    status.write(&mut out).expect("writing to a String can't fail");
+    out
+}
+
+fn written_markers(markers: &MarkerList) -> String {
+    let mut out = String::new();
+    markers.write(&mut out).expect("writing to a String can't fail");
+    out
+}
+
+#[test]
+fn recorded_response_round_trips_byte_for_byte() {
+    let status = ReaperStatus::<MAX_TRACK_COUNT>::parse(RECORDED_RESPONSE).expect("valid response");
+    assert_eq!(status.tracks[1].name.as_str(), "Kick\tIn");
+    assert_eq!(written(&status), RECORDED_RESPONSE);
+}
+
+#[test]
+fn recorded_markers_round_trip_byte_for_byte() {
+    let markers = MarkerList::parse(RECORDED_MARKERS).expect("valid response");
+    assert_eq!(markers.regions[0].name.as_str(), "verse");
+    assert_eq!(written_markers(&markers), RECORDED_MARKERS);
+}
+
+#[test]
+fn records_write_back_their_own_line() {
+    RECORDED_RESPONSE.lines().chain(RECORDED_MARKERS.lines()).for_each(|line| {
+        let mut out = String::new();
+        Record::parse(line)
+            .expect("valid line")
+            .write(&mut out)
+            .expect("writing to a String can't fail");
+        assert_eq!(out, line);
+    });
+    let mut out = String::new();
+    Record::parse("LASTTOUCHED\t1\t2")
+        .expect("unknown commands are skipped, not errors")
+        .write(&mut out)
+        .expect("writing to a String can't fail");
+    assert_eq!(out, "LASTTOUCHED");
+}
+
+#[test]
+fn missing_transport_is_an_error_only_when_asked_for() {
+    let tracks = RECORDED_RESPONSE
+        .lines()
+        .filter(|line| line.starts_with("TRACK\t"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let parse = |query: &ReaperQuery| ReaperStatus::<MAX_TRACK_COUNT>::parse_response(&tracks, query);
+    assert!(ReaperStatus::<MAX_TRACK_COUNT>::parse(&tracks).is_err());
+    assert!(parse(&ReaperQuery::new().transport().all_tracks()).is_err());
+    assert_eq!(parse(&ReaperQuery::new().all_tracks()).map(|status| status.tracks.len()), Ok(3));
+}
+
+#[test]
+fn trackflags_keep_undocumented_bits() {
+    // main.js: &512=hide from TCP, &1024=hide from MCP; 2048 isn't documented
+    let response = "TRANSPORT\t0\t0.000000\t0\t\t\nTRACK\t1\tPads\t3072\t1.000000\t0.000000\t-1500\t-1500\t1.000000\t3\t0\t0\t0\t0\n";
+    let status = ReaperStatus::<MAX_TRACK_COUNT>::parse(response).expect("valid response");
+    let flags = status.tracks[0].flags;
+    assert_eq!(flags.known(), TrackFlags::HiddenFromMcp);
+    assert!(!flags.contains(TrackFlags::HiddenFromTcp));
+    assert_eq!(flags.unknown(), 2048);
+    assert_eq!(written(&status), response);
+}
+
+/// floats are generated in micro-units so every value survives the 6 decimal places of the wire format
+fn micros(range: core::ops::Range<i64>) -> impl Strategy<Value = f64> {
+    range.prop_map(|micros| micros as f64 / 1_000_000.)
+}
+
+fn play_state() -> impl Strategy<Value = PlayState> {
+    any::<u8>().prop_map(PlayState::from_repr)
+}
+
+fn name<const SIZE: usize>() -> impl Strategy<Value = heapless::String<SIZE>> {
+    "[a-zA-Z0-9 ąę\\\\\t\n]{0,8}".prop_map(|name| heapless::String::try_from(name.as_str()).expect("8 chars of at most 2 bytes fit"))
+}
+
+fn position_string() -> impl Strategy<Value = reaper::PositionString> {
+    "[0-9:.]{0,16}".prop_map(|value| reaper::PositionString::try_from(value.as_str()).expect("16 bytes fit"))
+}
+
+fn transport() -> impl Strategy<Value = TransportData> {
+    (play_state(), micros(0..10_000_000_000), any::<bool>(), position_string(), position_string()).prop_map(
+        |(play_state, position_seconds, repeat_on, position_string, position_string_beats)| TransportData {
+            play_state,
+            position_seconds,
+            repeat_on,
+            position_string,
+            position_string_beats,
+        },
+    )
+}
+
+fn beat_position() -> impl Strategy<Value = BeatPosition> {
+    (play_state(), micros(0..10_000_000_000), micros(0..10_000_000_000), 0..10_000i32, micros(0..16_000_000), 1..16u8, 1..32u8).prop_map(
+        |(play_state, position_seconds, full_beat_position, measure_count, beats_in_measure, time_signature_numerator, time_signature_denominator)| BeatPosition {
+            play_state,
+            position_seconds,
+            full_beat_position,
+            measure_count,
+            beats_in_measure,
+            time_signature_numerator,
+            time_signature_denominator,
+        },
+    )
+}
+
+fn track() -> impl Strategy<Value = TrackData> {
+    (
+        (any::<u16>(), name(), any::<u16>(), micros(0..4_000_000), micros(-1_000_000..1_000_001)),
+        (any::<i16>(), any::<i16>(), micros(-1_000_000..1_000_001), any::<u8>()),
+        (any::<u16>(), any::<u16>(), any::<u16>(), any::<u32>(), proptest::option::of(any::<i8>())),
+    )
+        .prop_map(
+            |(
+                (track_number, name, flags, volume, pan),
+                (last_meter_peak, last_meter_pos, width, pan_mode),
+                (send_count, receive_count, hw_out_count, color, folder_depth),
+            )| TrackData {
+                track_number,
+                name,
+                flags: RawTrackFlags(flags),
+                volume: LinearGain(volume as f32),
+                pan: pan as f32,
+                last_meter_peak: Decibel(last_meter_peak),
+                last_meter_pos: Decibel(last_meter_pos),
+                width: width as f32,
+                pan_mode,
+                send_count,
+                receive_count,
+                hw_out_count,
+                color: TrackColor(color),
+                folder_depth,
+            },
+        )
+}
+
+fn markers() -> impl Strategy<Value = MarkerList> {
+    let marker = (name(), any::<u32>(), micros(0..10_000_000_000), any::<u32>()).prop_map(|(name, id, position_seconds, color)| Marker {
+        name,
+        id,
+        position_seconds,
+        color: TrackColor(color),
+    });
+    let region = (name(), any::<u32>(), micros(0..10_000_000_000), micros(0..10_000_000_000), any::<u32>()).prop_map(
+        |(name, id, start_seconds, end_seconds, color)| Region {
+            name,
+            id,
+            start_seconds,
+            end_seconds,
+            color: TrackColor(color),
+        },
+    );
+    (
+        proptest::collection::vec(marker, 0..=reaper::MAX_MARKER_COUNT),
+        proptest::collection::vec(region, 0..=reaper::MAX_REGION_COUNT),
+    )
+        .prop_map(|(markers, regions)| MarkerList {
+            markers: markers.into_iter().collect(),
+            regions: regions.into_iter().collect(),
+        })
+}
+
+fn status() -> impl Strategy<Value = ReaperStatus<MAX_TRACK_COUNT>> {
+    (
+        transport(),
+        proptest::option::of(beat_position()),
+        proptest::option::of(any::<u16>()),
+        proptest::collection::vec(track(), 0..=MAX_TRACK_COUNT),
+    )
+        .prop_map(|(transport, beat_position, track_count, tracks)| ReaperStatus {
+            transport,
+            beat_position,
+            track_count,
+            tracks: tracks.into_iter().collect(),
+        })
+}
+
+proptest! {
+    #[test]
+    fn parse_inverts_write(status in status()) {
+        let written = written(&status);
+        let parsed = ReaperStatus::<MAX_TRACK_COUNT>::parse(&written).map_err(TestCaseError::fail)?;
+        prop_assert_eq!(parsed, status);
+    }
+
+    #[test]
+    fn write_inverts_parse(status in status()) {
+        let written = written(&status);
+        let parsed = ReaperStatus::<MAX_TRACK_COUNT>::parse(&written).map_err(TestCaseError::fail)?;
+        prop_assert_eq!(self::written(&parsed), written);
+    }
+
+    #[test]
+    fn marker_list_parse_inverts_write(markers in markers()) {
+        let parsed = MarkerList::parse(&written_markers(&markers)).map_err(TestCaseError::fail)?;
+        prop_assert_eq!(parsed, markers);
+    }
+}
@@ -0,0 +1,276 @@
+//! Polls a Reaper (or `reaper-mock`) with the firmware's client and renders the panel into the terminal.
+//!
+//! Needs a terminal with truecolor support, and has to be built for the host, e.g.
+//! `cargo run --target x86_64-unknown-linux-gnu -- http://127.0.0.1:8080 --png-dir frames`.
+//! Sessions can be recorded with `--record` and played back later with `--replay`.
+
+use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+use reaper::{FolderDepths, ReaperStatus, Replay};
+use reaper_client::{
+    recorder::Recorder,
+    std_adapter::{block_on, StdDns, StdTcp},
+};
+use renderer::{BallisticsConfig, ClipLatches, ClipResetPolicy, Framebuffer, MeterBank, ReaperStatusRenderExt, RenderOptions};
+use reqwless::client::HttpClient;
+use std::{
+    fs::File,
+    io::Write as _,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+mod output;
+
+// the same sizes the firmware uses
+const MAX_TRACK_COUNT: usize = 64;
+const MAX_LINE_SIZE: usize = 256;
+const BODY_CHUNK_SIZE: usize = 256;
+const RX_BUFFER_SIZE: usize = 512 + BODY_CHUNK_SIZE;
+const MAX_PATH_SIZE: usize = 512;
+const FOLDER_DEPTH_REFRESH_POLLS: usize = 200;
+
+const PANEL_SIZE: usize = 64;
+
+type ReaperClient<'stack> = reaper_client::ReaperClient<'stack, StdTcp, MAX_TRACK_COUNT, MAX_LINE_SIZE, RX_BUFFER_SIZE, BODY_CHUNK_SIZE, MAX_PATH_SIZE>;
+type Panel = Framebuffer<PANEL_SIZE, PANEL_SIZE>;
+
+const USAGE: &str = "\
+usage: renderer-tester [BASE_URL] [OPTIONS]
+
+polls BASE_URL (default $ESP_REAPER_BASE_URL, then http://127.0.0.1:8080) and draws the panel
+
+options:
+  --interval-ms MILLIS    time between two polls (default 50)
+  --frames N              quit after N frames
+  --png-dir DIR           also write every frame to DIR/frame-NNNNNN.png
+  --png-scale N           size of a panel pixel in the png files (default 8)
+  --record FILE           write every response to FILE
+  --replay FILE           play a recorded session instead of polling
+  --speed FACTOR          replay speed, 2 plays twice as fast (default 1)
+";
+
+struct Options {
+    base_url: String,
+    interval: Duration,
+    frames: Option<usize>,
+    png_dir: Option<PathBuf>,
+    png_scale: usize,
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    speed: f64,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        base_url: std::env::var("ESP_REAPER_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:8080".to_owned()),
+        interval: Duration::from_millis(50),
+        frames: None,
+        png_dir: None,
+        png_scale: 8,
+        record: None,
+        replay: None,
+        speed: 1.,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} takes a value"));
+        let number = |name: &str, value: String| value.parse::<usize>().map_err(|_| format!("{name} takes a number"));
+        match arg.as_str() {
+            "--interval-ms" => options.interval = Duration::from_millis(number(&arg, value(&arg)?)? as u64),
+            "--frames" => options.frames = Some(number(&arg, value(&arg)?)?),
+            "--png-dir" => options.png_dir = Some(value(&arg)?.into()),
+            "--png-scale" => options.png_scale = number(&arg, value(&arg)?)?,
+            "--record" => options.record = Some(value(&arg)?.into()),
+            "--replay" => options.replay = Some(value(&arg)?.into()),
+            "--speed" => {
+                options.speed = value(&arg)?
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|speed| *speed > 0.)
+                    .ok_or("--speed takes a positive number")?
+            }
+            "-h" | "--help" => return Err(USAGE.to_owned()),
+            other if other.starts_with('-') => return Err(format!("unknown option {other}\n\n{USAGE}")),
+            other => other.clone_into(&mut options.base_url),
+        }
+    }
+    Ok(options)
+}
+
+/// Everything that outlives a connection: the smoothed meters, the last status and where frames go.
+struct Simulator {
+    options: Options,
+    started_at: Instant,
+    meters: MeterBank<MAX_TRACK_COUNT>,
+    clips: ClipLatches<MAX_TRACK_COUNT>,
+    status: ReaperStatus<MAX_TRACK_COUNT>,
+    panel: Panel,
+    frame: usize,
+    recorder: Option<Recorder<File>>,
+}
+
+impl Simulator {
+    fn now(&self) -> u64 {
+        self.started_at.elapsed().as_millis() as u64
+    }
+
+    fn update(&mut self, status: ReaperStatus<MAX_TRACK_COUNT>) {
+        let now = self.now();
+        self.meters.update(&status, now);
+        self.clips.update(&status, now);
+        self.status = status;
+    }
+
+    /// renders the last status like the firmware does and shows it, `message` goes below the panel
+    fn draw(&mut self, message: &str) -> Result<(), String> {
+        let now = self.now();
+        self.meters.advance(now);
+        self.clips.advance(now);
+        self.panel.clear(Rgb888::BLACK).ok();
+        self.status
+            .render_with_options(
+                &mut self.panel,
+                &RenderOptions {
+                    meters: Some(&self.meters),
+                    clips: Some(&self.clips),
+                    ..Default::default()
+                },
+            )
+            .map_err(|error| format!("rendering: {error}"))?;
+
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{}{}\x1b[K{message}", output::CURSOR_HOME, output::to_truecolor(&self.panel))
+            .and_then(|_| stdout.flush())
+            .map_err(|error| format!("writing to the terminal: {error}"))?;
+        if let Some(png_dir) = &self.options.png_dir {
+            let path = png_dir.join(format!("frame-{:06}.png", self.frame));
+            output::write_png(&self.panel, &path, self.options.png_scale).map_err(|error| format!("writing {}: {error}", path.display()))?;
+        }
+        self.frame += 1;
+        Ok(())
+    }
+
+    fn done(&self) -> bool {
+        self.options.frames.is_some_and(|frames| self.frame >= frames)
+    }
+
+    /// polls over one keep-alive connection until it breaks. The client reads a response that fails to parse
+    /// to its end, so only a network error leaves the connection in an unknown state, and that fails the next poll too.
+    fn run_connection(&mut self, client: &mut ReaperClient<'_>) -> Result<(), String> {
+        let mut polls = 0usize;
+        while !self.done() {
+            if polls.is_multiple_of(FOLDER_DEPTH_REFRESH_POLLS) {
+                // folders only change the layout, so a failed refresh keeps the depths already known and the connection
+                let refreshed = match &mut self.recorder {
+                    Some(recorder) => block_on(client.refresh_folder_depths_recorded(recorder)),
+                    None => block_on(client.refresh_folder_depths()),
+                };
+                if let Err(error) = refreshed {
+                    eprintln!("refreshing folder depths: {error}");
+                }
+            }
+            polls = polls.wrapping_add(1);
+            let status = match &mut self.recorder {
+                Some(recorder) => block_on(client.get_status_recorded(recorder)),
+                None => block_on(client.get_status()),
+            }
+            .map_err(|error| format!("polling: {error}"))?;
+            let message = format!(
+                "{}  {:?}  {} tracks  frame {}",
+                self.options.base_url,
+                status.transport.play_state,
+                status.track_count.unwrap_or(status.tracks.len().saturating_sub(1) as u16),
+                self.frame
+            );
+            self.update(status);
+            self.draw(&message)?;
+            thread::sleep(self.options.interval);
+        }
+        Ok(())
+    }
+
+    /// feeds the recorded responses in on their original schedule, scaled by `--speed`
+    fn replay(&mut self, path: &Path) -> Result<(), String> {
+        let recording = std::fs::read(path).map_err(|error| format!("reading {}: {error}", path.display()))?;
+        let mut replay = Replay::new(&recording, self.options.speed);
+        let mut folder_depths = FolderDepths::<MAX_TRACK_COUNT>::new();
+        let started_at = Instant::now();
+        let mut message = String::new();
+        while !replay.is_finished() && !self.done() {
+            let now = started_at.elapsed().as_millis() as u64;
+            while let Some(response) = replay.next_due(now) {
+                match response.and_then(|response| folder_depths.replay(&response)) {
+                    // a folder depth refresh only shows up in the statuses after it
+                    Ok(None) => {}
+                    Ok(Some(status)) => {
+                        message = format!(
+                            "{}  {}x  {:?}  {now} ms  frame {}",
+                            path.display(),
+                            self.options.speed,
+                            status.transport.play_state,
+                            self.frame
+                        );
+                        self.update(status);
+                    }
+                    // the live client fails on the same response, so it stays on the previous status too
+                    Err(error) => message = format!("{}  {now} ms  {error}", path.display()),
+                }
+            }
+            self.draw(&message)?;
+            thread::sleep(self.options.interval);
+        }
+        Ok(())
+    }
+
+    fn run(&mut self) -> Result<(), String> {
+        if let Some(png_dir) = &self.options.png_dir {
+            std::fs::create_dir_all(png_dir).map_err(|error| format!("creating {}: {error}", png_dir.display()))?;
+        }
+        print!("{}", output::CLEAR_SCREEN);
+        if let Some(path) = self.options.replay.clone() {
+            return self.replay(&path);
+        }
+        if let Some(path) = &self.options.record {
+            self.recorder = File::create(path)
+                .map(Recorder::new)
+                .map(Some)
+                .map_err(|error| format!("creating {}: {error}", path.display()))?;
+        }
+        let base_url = self.options.base_url.clone();
+        let (tcp, dns) = (StdTcp, StdDns);
+        while !self.done() {
+            let mut http_client = HttpClient::new(&tcp, &dns);
+            let result = block_on(ReaperClient::new(&mut http_client, &base_url))
+                .map_err(|error| format!("connecting: {error}"))
+                .and_then(|mut client| self.run_connection(&mut client));
+            if let Err(message) = result {
+                // keep the last frame on screen, the meters still fall while reconnecting
+                self.draw(&format!("{base_url}  {message}, reconnecting"))?;
+                thread::sleep(self.options.interval.max(Duration::from_millis(500)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|message| {
+        eprintln!("{message}");
+        std::process::exit(2);
+    });
+    let mut simulator = Simulator {
+        options,
+        started_at: Instant::now(),
+        meters: MeterBank::new(BallisticsConfig::default()),
+        clips: ClipLatches::new(ClipResetPolicy::default()),
+        status: ReaperStatus::default(),
+        panel: Panel::default(),
+        frame: 0,
+        recorder: None,
+    };
+    if let Err(message) = simulator.run() {
+        eprintln!("{message}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,48 @@
+use embedded_graphics::prelude::*;
+use renderer::Framebuffer;
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+};
+
+/// moves the cursor to the top left corner, so every frame overwrites the previous one
+pub const CURSOR_HOME: &str = "\x1b[H";
+pub const CLEAR_SCREEN: &str = "\x1b[2J";
+
+/// Draws two rows of pixels per line of text: the foreground of `▀` is the upper pixel, the background the lower one.
+pub fn to_truecolor<const WIDTH: usize, const HEIGHT: usize>(frame: &Framebuffer<WIDTH, HEIGHT>) -> String {
+    let rows = frame.rows().collect::<Vec<_>>();
+    let mut out = String::new();
+    rows.chunks(2).for_each(|pair| {
+        (0..WIDTH).for_each(|x| {
+            let upper = pair[0][x];
+            match pair.get(1).map(|lower| lower[x]) {
+                Some(lower) => write!(out, "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m▀", upper.r(), upper.g(), upper.b(), lower.r(), lower.g(), lower.b()),
+                None => write!(out, "\x1b[38;2;{};{};{}m\x1b[49m▀", upper.r(), upper.g(), upper.b()),
+            }
+            .ok();
+        });
+        out.push_str("\x1b[0m\n");
+    });
+    out
+}
+
+/// writes `frame` as an RGB png, every pixel blown up to a `scale` by `scale` square so the panel is easy to look at
+pub fn write_png<const WIDTH: usize, const HEIGHT: usize>(frame: &Framebuffer<WIDTH, HEIGHT>, path: &Path, scale: usize) -> io::Result<()> {
+    let scale = scale.max(1);
+    let data = frame
+        .rows()
+        .flat_map(|row| std::iter::repeat_n(row, scale))
+        .flat_map(|row| row.iter().flat_map(|pixel| std::iter::repeat_n([pixel.r(), pixel.g(), pixel.b()], scale)))
+        .flatten()
+        .collect::<Vec<_>>();
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), (WIDTH * scale) as u32, (HEIGHT * scale) as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(io::Error::other)
+}
@@ -0,0 +1,204 @@
+//! Time-aware smoothing of meter values between network updates.
+//!
+//! Timestamps are plain milliseconds from any monotonic clock, so the same
+//! code runs against `embassy_time::Instant` on the device and against
+//! made-up timestamps on the host.
+
+use crate::METER_FLOOR;
+use reaper::{Decibel, ReaperStatus};
+
+/// Milliseconds from an arbitrary, monotonic starting point.
+pub type Millis = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BallisticsConfig {
+    /// time constant of the rise towards a louder level, `0` jumps straight to it
+    pub attack_millis: u32,
+    /// how fast the bar falls once the signal gets quieter
+    pub release_db_per_second: f32,
+    /// how long the peak marker stays put before it starts falling
+    pub peak_hold_millis: u32,
+    pub peak_fall_db_per_second: f32,
+}
+
+impl BallisticsConfig {
+    /// instant attack with a slow release and held peaks, like most DAW meters
+    pub const DIGITAL: Self = Self {
+        attack_millis: 0,
+        release_db_per_second: 20.,
+        peak_hold_millis: 1500,
+        peak_fall_db_per_second: 20.,
+    };
+    /// BBC style PPM: 10ms integration and 24dB fall in 2.8s
+    pub const PPM: Self = Self {
+        attack_millis: 10,
+        release_db_per_second: 8.6,
+        peak_hold_millis: 1000,
+        peak_fall_db_per_second: 8.6,
+    };
+    /// roughly 300ms to settle in both directions, no peak hold
+    pub const VU: Self = Self {
+        attack_millis: 65,
+        release_db_per_second: 65.,
+        peak_hold_millis: 0,
+        peak_fall_db_per_second: 65.,
+    };
+}
+
+impl Default for BallisticsConfig {
+    fn default() -> Self {
+        Self::DIGITAL
+    }
+}
+
+/// What a meter should show right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeterReading {
+    pub level: Decibel,
+    pub peak: Decibel,
+}
+
+/// where meters start and the lowest level they fall to, `as_db` is not a `const fn`
+fn floor_db() -> f32 {
+    METER_FLOOR.as_db()
+}
+
+/// Ballistics state machine of a single meter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeterBallistics {
+    target_db: f32,
+    level_db: f32,
+    peak_db: f32,
+    peak_held_at: Millis,
+    last_update: Option<Millis>,
+}
+
+impl Default for MeterBallistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MeterBallistics {
+    pub fn new() -> Self {
+        Self {
+            target_db: floor_db(),
+            level_db: floor_db(),
+            peak_db: floor_db(),
+            peak_held_at: 0,
+            last_update: None,
+        }
+    }
+
+    /// feeds a fresh value from the network, `peak` is Reaper's own peak since the last poll
+    pub fn set_target(&mut self, config: &BallisticsConfig, level: Decibel, peak: Decibel, now: Millis) {
+        self.advance(config, now);
+        self.target_db = level.as_db().max(floor_db());
+        self.hold_peak(peak.as_db().max(self.target_db), now);
+    }
+
+    /// moves the meter forward to `now`, going back in time is ignored
+    pub fn advance(&mut self, config: &BallisticsConfig, now: Millis) -> MeterReading {
+        let previous = self.last_update.unwrap_or(now).min(now);
+        self.last_update = Some(self.last_update.map_or(now, |last_update| last_update.max(now)));
+        let elapsed_seconds = (now - previous) as f32 / 1000.;
+
+        self.level_db = match (self.target_db > self.level_db, config.attack_millis) {
+            (true, 0) => self.target_db,
+            (true, attack_millis) => {
+                let approach = 1. - libm::expf(-elapsed_seconds * 1000. / attack_millis as f32);
+                self.level_db + (self.target_db - self.level_db) * approach
+            }
+            (false, _) => (self.level_db - config.release_db_per_second * elapsed_seconds).max(self.target_db),
+        };
+
+        // the peak only falls for the part of this step that is past its hold time
+        let hold_ends_at = self.peak_held_at + config.peak_hold_millis as Millis;
+        let falling_seconds = now.saturating_sub(previous.max(hold_ends_at)) as f32 / 1000.;
+        self.peak_db = (self.peak_db - config.peak_fall_db_per_second * falling_seconds).max(self.level_db);
+        self.hold_peak(self.level_db, now);
+
+        self.reading()
+    }
+
+    fn hold_peak(&mut self, db: f32, now: Millis) {
+        if db >= self.peak_db {
+            self.peak_db = db;
+            self.peak_held_at = now;
+        }
+    }
+
+    pub fn reading(&self) -> MeterReading {
+        MeterReading {
+            level: Decibel::from_db(self.level_db),
+            peak: Decibel::from_db(self.peak_db),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TrackMeter {
+    track_number: u16,
+    ballistics: MeterBallistics,
+}
+
+/// One [`MeterBallistics`] per track of a [`ReaperStatus`], matched by track number,
+/// so peak hold and decay stay with their track when tracks get inserted or deleted before it.
+#[derive(Debug, Clone)]
+pub struct MeterBank<const MAX_TRACK_COUNT: usize> {
+    pub config: BallisticsConfig,
+    meters: heapless::Vec<TrackMeter, MAX_TRACK_COUNT>,
+}
+
+impl<const MAX_TRACK_COUNT: usize> MeterBank<MAX_TRACK_COUNT> {
+    pub const fn new(config: BallisticsConfig) -> Self {
+        Self {
+            config,
+            meters: heapless::Vec::new(),
+        }
+    }
+
+    /// feeds the meters of every track in `status`, tracks that are gone from it are forgotten
+    pub fn update(&mut self, status: &ReaperStatus<MAX_TRACK_COUNT>, now: Millis) {
+        let config = self.config;
+        self.meters
+            .retain(|meter| status.track(meter.track_number).is_some());
+        status.tracks.iter().for_each(|track| {
+            let meter = match self
+                .meters
+                .iter()
+                .position(|meter| meter.track_number == track.track_number)
+            {
+                Some(position) => self.meters.get_mut(position),
+                // can't be full, only tracks of `status` are kept
+                None => self
+                    .meters
+                    .push(TrackMeter {
+                        track_number: track.track_number,
+                        ballistics: MeterBallistics::new(),
+                    })
+                    .ok()
+                    .and_then(|_| self.meters.last_mut()),
+            };
+            if let Some(meter) = meter {
+                meter
+                    .ballistics
+                    .set_target(&config, track.last_meter_pos, track.last_meter_peak, now);
+            }
+        });
+    }
+
+    pub fn advance(&mut self, now: Millis) {
+        let config = self.config;
+        self.meters.iter_mut().for_each(|meter| {
+            meter.ballistics.advance(&config, now);
+        });
+    }
+
+    pub fn reading(&self, track_number: u16) -> Option<MeterReading> {
+        self.meters
+            .iter()
+            .find(|meter| meter.track_number == track_number)
+            .map(|meter| meter.ballistics.reading())
+    }
+}
@@ -0,0 +1,111 @@
+//! Clip indicators that stay lit until they get reset.
+//!
+//! Reaper only reports the meter values of the moment it got polled, so a
+//! short overload is gone with the next status. [`ClipLatches`] remembers
+//! every track that clipped since the last reset.
+
+use crate::Millis;
+use reaper::ReaperStatus;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClipResetPolicy {
+    /// forget all clips when the transport starts rolling, so every take starts clean
+    pub on_transport_start: bool,
+    /// forget a clip once the track hasn't clipped for this long, `None` keeps it until reset
+    pub timeout_millis: Option<u32>,
+}
+
+impl Default for ClipResetPolicy {
+    fn default() -> Self {
+        Self {
+            on_transport_start: true,
+            timeout_millis: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClipLatch {
+    track_number: u16,
+    last_clipped_at: Millis,
+}
+
+/// The tracks that clipped since the last reset, matched by their track number.
+#[derive(Debug, Clone)]
+pub struct ClipLatches<const MAX_TRACK_COUNT: usize> {
+    pub policy: ClipResetPolicy,
+    latches: heapless::Vec<ClipLatch, MAX_TRACK_COUNT>,
+    was_rolling: bool,
+}
+
+impl<const MAX_TRACK_COUNT: usize> ClipLatches<MAX_TRACK_COUNT> {
+    pub const fn new(policy: ClipResetPolicy) -> Self {
+        Self {
+            policy,
+            latches: heapless::Vec::new(),
+            was_rolling: false,
+        }
+    }
+
+    /// latches every track that clips in `status`, tracks that are gone from it are forgotten
+    pub fn update(&mut self, status: &ReaperStatus<MAX_TRACK_COUNT>, now: Millis) {
+        let is_rolling = status.transport.play_state.is_rolling();
+        if self.policy.on_transport_start && is_rolling && !self.was_rolling {
+            self.reset();
+        }
+        self.was_rolling = is_rolling;
+
+        self.latches
+            .retain(|latch| status.track(latch.track_number).is_some());
+        status
+            .tracks
+            .iter()
+            .filter(|track| track.is_clipping())
+            .for_each(|track| {
+                match self
+                    .latches
+                    .iter_mut()
+                    .find(|latch| latch.track_number == track.track_number)
+                {
+                    Some(latch) => latch.last_clipped_at = now,
+                    // can't be full, only tracks of `status` are kept
+                    None => {
+                        self.latches
+                            .push(ClipLatch {
+                                track_number: track.track_number,
+                                last_clipped_at: now,
+                            })
+                            .ok();
+                    }
+                }
+            });
+        self.advance(now);
+    }
+
+    /// drops the clips that timed out by `now`
+    pub fn advance(&mut self, now: Millis) {
+        if let Some(timeout_millis) = self.policy.timeout_millis {
+            self.latches
+                .retain(|latch| now.saturating_sub(latch.last_clipped_at) < timeout_millis as Millis);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.latches.clear();
+    }
+
+    pub fn reset_track(&mut self, track_number: u16) {
+        self.latches
+            .retain(|latch| latch.track_number != track_number);
+    }
+
+    pub fn is_latched(&self, track_number: u16) -> bool {
+        self.latches
+            .iter()
+            .any(|latch| latch.track_number == track_number)
+    }
+
+    pub fn latched_tracks(&self) -> impl Iterator<Item = u16> + '_ {
+        self.latches.iter().map(|latch| latch.track_number)
+    }
+}
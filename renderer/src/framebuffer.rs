@@ -0,0 +1,121 @@
+//! An in-memory panel for host tools and tests, with text and image encodings of what was drawn.
+
+use crate::ColorType;
+use embedded_graphics::{
+    geometry::{OriginDimensions, Size},
+    pixelcolor::RgbColor,
+    draw_target::DrawTarget,
+    Pixel,
+};
+use std::{convert::Infallible, fmt::Write as _};
+
+/// characters for the colors the renderer draws with, in [`Framebuffer::to_ascii`]
+const PALETTE: [(ColorType, char); 8] = [
+    (ColorType::BLACK, '.'),
+    (ColorType::WHITE, 'W'),
+    (ColorType::RED, 'R'),
+    (ColorType::GREEN, 'G'),
+    (ColorType::BLUE, 'B'),
+    (ColorType::YELLOW, 'Y'),
+    (ColorType::CYAN, 'C'),
+    (ColorType::MAGENTA, 'M'),
+];
+
+/// characters for any other color (e.g. track colors), handed out in order of appearance
+const OTHER_COLORS: &str = "abcdefghijklmnopqrstuvwxyz0123456789";
+
+/// The panel in memory, pixels outside of it are dropped just like the hub75 driver does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Framebuffer<const WIDTH: usize, const HEIGHT: usize> {
+    pixels: Vec<ColorType>,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Default for Framebuffer<WIDTH, HEIGHT> {
+    fn default() -> Self {
+        Self {
+            pixels: vec![ColorType::BLACK; WIDTH * HEIGHT],
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Framebuffer<WIDTH, HEIGHT> {
+    pub fn pixel(&self, x: usize, y: usize) -> ColorType {
+        self.pixels[y * WIDTH + x]
+    }
+
+    /// rows from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[ColorType]> + '_ {
+        self.pixels.chunks(WIDTH)
+    }
+
+    /// One character per pixel, a row per line. The named colors get fixed characters (`.` is black,
+    /// `G` green and so on), every other color a lowercase letter or digit, listed below the picture
+    /// as `<character> #rrggbb`. Colors past the 36th all share `?`.
+    pub fn to_ascii(&self) -> String {
+        let mut others = Vec::<ColorType>::new();
+        let mut out = String::with_capacity((WIDTH + 1) * HEIGHT);
+        self.rows().for_each(|row| {
+            row.iter().for_each(|color| {
+                let character = PALETTE
+                    .iter()
+                    .find(|(named, _)| named == color)
+                    .map(|(_, character)| *character)
+                    .unwrap_or_else(|| {
+                        let index = others
+                            .iter()
+                            .position(|other| other == color)
+                            .unwrap_or_else(|| {
+                                others.push(*color);
+                                others.len() - 1
+                            });
+                        OTHER_COLORS.chars().nth(index).unwrap_or('?')
+                    });
+                out.push(character);
+            });
+            out.push('\n');
+        });
+        others
+            .iter()
+            .zip(OTHER_COLORS.chars())
+            .for_each(|(color, character)| {
+                writeln!(out, "{character} #{:02x}{:02x}{:02x}", color.r(), color.g(), color.b()).ok();
+            });
+        out
+    }
+
+    /// a binary (`P6`) portable pixmap, which most image viewers open
+    pub fn to_ppm(&self) -> Vec<u8> {
+        format!("P6\n{WIDTH} {HEIGHT}\n255\n")
+            .into_bytes()
+            .into_iter()
+            .chain(self.pixels.iter().flat_map(|color| [color.r(), color.g(), color.b()]))
+            .collect()
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> OriginDimensions for Framebuffer<WIDTH, HEIGHT> {
+    fn size(&self) -> Size {
+        Size::new(WIDTH as u32, HEIGHT as u32)
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> DrawTarget for Framebuffer<WIDTH, HEIGHT> {
+    type Color = ColorType;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> core::result::Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        pixels
+            .into_iter()
+            .filter(|Pixel(point, _)| (0..WIDTH as i32).contains(&point.x) && (0..HEIGHT as i32).contains(&point.y))
+            .for_each(|Pixel(point, color)| self.pixels[point.y as usize * WIDTH + point.x as usize] = color);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> core::result::Result<(), Self::Error> {
+        self.pixels.fill(color);
+        Ok(())
+    }
+}
@@ -2,18 +2,59 @@
 
 use embedded_graphics::{
     geometry::{Point, Size},
-    pixelcolor::{RgbColor, WebColors},
+    pixelcolor::RgbColor,
     primitives::{Primitive, PrimitiveStyle, Rectangle},
     Drawable,
 };
 use embedded_wrap_err::{IntoWrapErrDebugExt, Result, WrapErrorExt};
-use reaper::{PlayState, ReaperStatus, TrackData, TrackFlags};
+use reaper::{ColorByteOrder, Decibel, FolderTree, ReaperStatus, TrackColor, TrackData, TrackFlags, TransportData};
 use tap::prelude::*;
 
+pub mod ballistics;
+pub mod clip;
+#[cfg(feature = "std")]
+pub mod framebuffer;
+
+pub use ballistics::{BallisticsConfig, MeterBallistics, MeterBank, MeterReading, Millis};
+pub use clip::{ClipLatches, ClipResetPolicy};
+#[cfg(feature = "std")]
+pub use framebuffer::Framebuffer;
+
 type ColorType = embedded_graphics::pixelcolor::Rgb888;
 
-pub fn minus_decibel_to_height(minus_decibel: i16) -> f32 {
-    1. + (minus_decibel.min(0) as f32 / 1500.)
+/// the bottom of every meter, which is also what Reaper reports for silence
+pub const METER_FLOOR: Decibel = Decibel::SILENCE;
+
+pub fn meter_height(level: Decibel) -> f32 {
+    level.meter_fraction(METER_FLOOR)
+}
+
+#[extension_traits::extension(pub trait TrackColorExt)]
+impl TrackColor {
+    fn to_rgb888(self, order: ColorByteOrder) -> Option<ColorType> {
+        self.rgb(order)
+            .map(|(red, green, blue)| ColorType::new(red, green, blue))
+    }
+}
+
+/// Everything besides the status itself that changes what gets drawn.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions<'a, const MAX_TRACK_COUNT: usize> {
+    pub color_order: ColorByteOrder,
+    /// smoothed levels and held peaks to draw instead of the raw values of the last poll
+    pub meters: Option<&'a MeterBank<MAX_TRACK_COUNT>>,
+    /// tracks that get their clip pixel lit
+    pub clips: Option<&'a ClipLatches<MAX_TRACK_COUNT>>,
+}
+
+impl<const MAX_TRACK_COUNT: usize> Default for RenderOptions<'_, MAX_TRACK_COUNT> {
+    fn default() -> Self {
+        Self {
+            color_order: ColorByteOrder::default(),
+            meters: None,
+            clips: None,
+        }
+    }
 }
 
 #[extension_traits::extension(pub trait ReaperStatusRenderExt)]
@@ -24,91 +65,183 @@ impl<const MAX_TRACK_COUNT: usize> ReaperStatus<MAX_TRACK_COUNT> {
         E: core::fmt::Debug,
         D: embedded_graphics::draw_target::DrawTarget<Color = ColorType, Error = E>,
     {
-        self.pipe(|Self { play_state, tracks }| -> Result<()> {
-            let status_color = match play_state {
-                PlayState::Stopped => ColorType::BLUE,
-                PlayState::Playing => ColorType::GREEN,
-                PlayState::Paused => ColorType::BLUE,
-                PlayState::Recording => ColorType::RED,
-                PlayState::RecordPaused => ColorType::BLUE,
-            };
-
-            const TRACK_LEVEL_COLOR: ColorType = ColorType::GREEN;
-            const TRACK_PEAK_COLOR: ColorType = ColorType::YELLOW;
-            const TRACK_PEAK_ERROR: ColorType = ColorType::RED;
-            const TRACK_MUTED_COLOR: ColorType = ColorType::CYAN;
-            // pub fn draw_state(&mut self, ReaperStatus { play_state, tracks }:
-            // ReaperStatus) -> Result<()> { println!("drawing state");
-            const TOTAL_HEIGHT: u32 = 64;
-            const TOTAL_WIDTH: u32 = 64;
-            const STATUS_BAR_HEIGHT: u32 = 2;
-            const MAX_TRACK_HEIGHT: u32 = TOTAL_HEIGHT - STATUS_BAR_HEIGHT;
-            // status bar
-            Rectangle::new(Point::new(0, 0), Size::new(TOTAL_WIDTH, STATUS_BAR_HEIGHT))
-                .into_styled(status_color.pipe(PrimitiveStyle::with_fill))
-                .draw(display)
-                .into_wrap_err_dbg("drawing status bar")?;
-
-            tracks
-                .iter()
-                .enumerate()
-                .try_for_each(|(index, TrackData { flags, last_meter_peak, last_meter_pos })| {
-                    let height = |decibel_value: i16| {
-                        ((minus_decibel_to_height(decibel_value) * MAX_TRACK_HEIGHT as f32) as u32).tap_dbg(|_height| {
-                            #[cfg(feature = "std")]
-                            println!("decibel_value: {decibel_value}; height: {_height}");
-                        })
-                    };
-                    let position = |index: usize, decibel_value| Point::new(index as _, (STATUS_BAR_HEIGHT + MAX_TRACK_HEIGHT - height(decibel_value)) as _);
-                    let rectangle = |index, decibel_value| Rectangle::new(position(index, decibel_value), Size::new(1, height(decibel_value)));
-                    let max_value = last_meter_pos.max(last_meter_peak);
-
-                    let is_peaking = last_meter_pos >= &0 || last_meter_peak >= &0;
-                    let track_color = match (*flags, is_peaking) {
-                        (flags, _) if flags.contains(TrackFlags::Muted) => TRACK_MUTED_COLOR,
-                        (_, true) => TRACK_PEAK_ERROR,
-                        (_, false) => TRACK_LEVEL_COLOR,
-                    };
-                    Ok(()).and_then(|_| {
-                        rectangle(index as _, *last_meter_pos)
+        self.render_with_color_order(display, ColorByteOrder::default())
+    }
+
+    /// same as `render`, but decodes track colors for a Reaper running on a specific OS
+    #[inline(always)]
+    fn render_with_color_order<E, D>(&self, display: &mut D, color_order: ColorByteOrder) -> Result<()>
+    where
+        E: core::fmt::Debug,
+        D: embedded_graphics::draw_target::DrawTarget<Color = ColorType, Error = E>,
+    {
+        self.render_with_options(display, &RenderOptions { color_order, ..Default::default() })
+    }
+
+    /// the most general form of `render`, see [`RenderOptions`]
+    #[inline(always)]
+    fn render_with_options<E, D>(&self, display: &mut D, options: &RenderOptions<'_, MAX_TRACK_COUNT>) -> Result<()>
+    where
+        E: core::fmt::Debug,
+        D: embedded_graphics::draw_target::DrawTarget<Color = ColorType, Error = E>,
+    {
+        render_status(self, display, options)
+    }
+}
+
+#[inline(always)]
+fn render_status<const MAX_TRACK_COUNT: usize, E, D>(status: &ReaperStatus<MAX_TRACK_COUNT>, display: &mut D, RenderOptions { color_order, meters, clips }: &RenderOptions<'_, MAX_TRACK_COUNT>) -> Result<()>
+where
+    E: core::fmt::Debug,
+    D: embedded_graphics::draw_target::DrawTarget<Color = ColorType, Error = E>,
+{
+    status.pipe(|ReaperStatus { transport: TransportData { play_state, .. }, beat_position, tracks, .. }| -> Result<()> {
+        let status_color = match (play_state.is_rolling(), play_state.is_recording()) {
+            (true, true) => ColorType::RED,
+            (true, false) => ColorType::GREEN,
+            (false, _) => ColorType::BLUE,
+        };
+        // flash the status bar at the start of every beat while the transport is rolling
+        const BEAT_FLASH_LENGTH: f64 = 0.25;
+        let status_color = match beat_position {
+            Some(beat_position) if play_state.is_rolling() && beat_position.beat_phase() < BEAT_FLASH_LENGTH => ColorType::WHITE,
+            _ => status_color,
+        };
+
+        const TRACK_LEVEL_COLOR: ColorType = ColorType::GREEN;
+        const TRACK_PEAK_COLOR: ColorType = ColorType::YELLOW;
+        const TRACK_PEAK_ERROR: ColorType = ColorType::RED;
+        const TRACK_MUTED_COLOR: ColorType = ColorType::CYAN;
+        const TRACK_CLIP_LATCH_COLOR: ColorType = ColorType::RED;
+        /// bus of a folder track without a color of its own
+        const BUS_COLOR: ColorType = ColorType::WHITE;
+        // pub fn draw_state(&mut self, ReaperStatus { play_state, tracks }:
+        // ReaperStatus) -> Result<()> { println!("drawing state");
+        const TOTAL_HEIGHT: u32 = 64;
+        const TOTAL_WIDTH: u32 = 64;
+        const STATUS_BAR_HEIGHT: u32 = 2;
+        /// a row of latched clip indicators right below the status bar
+        const CLIP_ROW_HEIGHT: u32 = 1;
+        /// a row in the color of the top level folder every meter is routed into
+        const BUS_ROW_HEIGHT: u32 = 1;
+        const BUS_ROW_TOP: u32 = STATUS_BAR_HEIGHT + CLIP_ROW_HEIGHT;
+        const METERS_TOP: u32 = BUS_ROW_TOP + BUS_ROW_HEIGHT;
+        const MAX_TRACK_HEIGHT: u32 = TOTAL_HEIGHT - METERS_TOP;
+        // status bar
+        Rectangle::new(Point::new(0, 0), Size::new(TOTAL_WIDTH, STATUS_BAR_HEIGHT))
+            .into_styled(status_color.pipe(PrimitiveStyle::with_fill))
+            .draw(display)
+            .into_wrap_err_dbg("drawing status bar")?;
+
+        // with more tracks than columns, nested folders collapse into their folder track, which meters the whole bus
+        let folder_tree = FolderTree::<MAX_TRACK_COUNT>::new(tracks);
+        let max_level = (tracks.len() > TOTAL_WIDTH as usize)
+            .then(|| folder_tree.deepest_level_fitting(TOTAL_WIDTH as usize))
+            .flatten();
+        let is_visible = |index: &usize| match max_level {
+            Some(max_level) => folder_tree
+                .nodes()
+                .get(*index)
+                .is_some_and(|node| node.level <= max_level),
+            None => true,
+        };
+        let bus = |index: usize| folder_tree.bus(index);
+        let visible = || (0..tracks.len()).filter(is_visible);
+        // meters of different buses are kept apart by a blank column, as long as that still fits
+        let bus_changes = visible()
+            .zip(visible().skip(1))
+            .filter(|(previous, index)| bus(*previous) != bus(*index))
+            .count();
+        let separate_buses = visible().count() + bus_changes <= TOTAL_WIDTH as usize;
+
+        visible()
+            .scan(None, |previous: &mut Option<(usize, usize)>, index| {
+                let column = match *previous {
+                    Some((previous_column, previous_index)) => previous_column + 1 + usize::from(separate_buses && bus(previous_index) != bus(index)),
+                    None => 0,
+                };
+                *previous = Some((column, index));
+                Some((column, index))
+            })
+            .take_while(|(column, _)| *column < TOTAL_WIDTH as usize)
+            .try_for_each(|(column, index)| {
+                let track @ TrackData { track_number, flags, last_meter_pos, color, .. } = &tracks[index];
+                let (level, peak) = meters
+                    .and_then(|meters| meters.reading(*track_number))
+                    .map(|MeterReading { level, peak }| (level, Some(peak)))
+                    .unwrap_or((*last_meter_pos, None));
+                let height = |level: Decibel| (meter_height(level) * MAX_TRACK_HEIGHT as f32) as u32;
+                let position = |column: usize, decibel_value| Point::new(column as _, (METERS_TOP + MAX_TRACK_HEIGHT - height(decibel_value)) as _);
+                let rectangle = |column, decibel_value| Rectangle::new(position(column, decibel_value), Size::new(1, height(decibel_value)));
+
+                let track_color = match (*flags, track.is_clipping()) {
+                    (flags, _) if flags.contains(TrackFlags::Muted) => TRACK_MUTED_COLOR,
+                    (_, true) => TRACK_PEAK_ERROR,
+                    (_, false) => color
+                        .to_rgb888(*color_order)
+                        .unwrap_or(TRACK_LEVEL_COLOR),
+                };
+                Ok(())
+                    .and_then(|_| {
+                        rectangle(column, level)
                             .into_styled(track_color.pipe(PrimitiveStyle::with_fill))
                             .draw(display)
                             .into_wrap_err_dbg("drawing track")
                     })
-                    // .and_then(|_| {
-                    //     rectangle(index as _, *last_meter_peak)
-                    //         .into_styled(TRACK_PEAK_COLOR.pipe(PrimitiveStyle::with_fill))
-                    //         .draw(display)
-                    //         .into_wrap_err_dbg("drawing track")
-                    // })
-                    // .and_then(|_| {
-                    //     max_value
-                    //         .ge(&0)
-                    //         .then(|| {
-                    //             rectangle(index as _, *max_value)
-                    //                 .into_styled(TRACK_PEAK_ERROR.pipe(PrimitiveStyle::with_fill))
-                    //                 .draw(display)
-                    //                 .into_wrap_err_dbg("drawing track")
-                    //         })
-                    //         .unwrap_or(Ok(()))
-                    // })
-                    // .and_then(|_| {
-                    //     flags
-                    //         .contains(TrackFlags::Muted)
-                    //         .then(|| {
-                    //             rectangle(index as _, *max_value)
-                    //                 .into_styled(TRACK_MUTED_COLOR.pipe(PrimitiveStyle::with_fill))
-                    //                 .draw(display)
-                    //                 .into_wrap_err_dbg("drawing track")
-                    //         })
-                    //         .unwrap_or(Ok(()))
-                    // })
-                })
-                .wrap_err("drawing all tracks")?;
-
-            Ok(())
-        })
-    }
+                    .and_then(|_| match peak {
+                        Some(peak) if height(peak) > height(level) => Rectangle::new(position(column, peak), Size::new(1, 1))
+                            .into_styled(TRACK_PEAK_COLOR.pipe(PrimitiveStyle::with_fill))
+                            .draw(display)
+                            .into_wrap_err_dbg("drawing peak hold"),
+                        _ => Ok(()),
+                    })
+                    .and_then(|_| match clips {
+                        Some(clips) if clips.is_latched(*track_number) => Rectangle::new(Point::new(column as _, STATUS_BAR_HEIGHT as _), Size::new(1, CLIP_ROW_HEIGHT))
+                            .into_styled(TRACK_CLIP_LATCH_COLOR.pipe(PrimitiveStyle::with_fill))
+                            .draw(display)
+                            .into_wrap_err_dbg("drawing clip indicator"),
+                        _ => Ok(()),
+                    })
+                    .and_then(|_| match bus(index) {
+                        Some(bus) => Rectangle::new(Point::new(column as _, BUS_ROW_TOP as _), Size::new(1, BUS_ROW_HEIGHT))
+                            .into_styled(
+                                tracks[bus.index]
+                                    .color
+                                    .to_rgb888(*color_order)
+                                    .unwrap_or(BUS_COLOR)
+                                    .pipe(PrimitiveStyle::with_fill),
+                            )
+                            .draw(display)
+                            .into_wrap_err_dbg("drawing bus"),
+                        None => Ok(()),
+                    })
+                // .and_then(|_| {
+                //     max_value
+                //         .ge(&0)
+                //         .then(|| {
+                //             rectangle(index as _, *max_value)
+                //                 .into_styled(TRACK_PEAK_ERROR.pipe(PrimitiveStyle::with_fill))
+                //                 .draw(display)
+                //                 .into_wrap_err_dbg("drawing track")
+                //         })
+                //         .unwrap_or(Ok(()))
+                // })
+                // .and_then(|_| {
+                //     flags
+                //         .contains(TrackFlags::Muted)
+                //         .then(|| {
+                //             rectangle(index as _, *max_value)
+                //                 .into_styled(TRACK_MUTED_COLOR.pipe(PrimitiveStyle::with_fill))
+                //                 .draw(display)
+                //                 .into_wrap_err_dbg("drawing track")
+                //         })
+                //         .unwrap_or(Ok(()))
+                // })
+            })
+            .wrap_err("drawing all tracks")?;
+
+        Ok(())
+    })
 }
 
 // fn graphics_demo(delay: &mut DelayUs<u8>, display: &mut MyMatrixDisplay) ->
@@ -0,0 +1,83 @@
+use reaper::{Decibel, ReaperStatus, TrackData};
+use renderer::{BallisticsConfig, MeterBallistics, MeterBank};
+
+const CONFIG: BallisticsConfig = BallisticsConfig {
+    attack_millis: 0,
+    release_db_per_second: 20.,
+    peak_hold_millis: 1000,
+    peak_fall_db_per_second: 10.,
+};
+
+#[test]
+fn instant_attack_jumps_to_louder_levels() {
+    let mut meter = MeterBallistics::new();
+    meter.set_target(&CONFIG, Decibel(-60), Decibel(-60), 0);
+    assert_eq!(meter.advance(&CONFIG, 0).level, Decibel(-60));
+}
+
+#[test]
+fn slow_attack_approaches_the_target() {
+    let config = BallisticsConfig { attack_millis: 100, ..CONFIG };
+    let mut meter = MeterBallistics::new();
+    meter.set_target(&config, Decibel(-100), Decibel(-100), 0);
+    let first = meter.advance(&config, 100).level;
+    let second = meter.advance(&config, 1000).level;
+    assert!(first < Decibel(-100) && first > Decibel(-1500), "{first:?}");
+    assert_eq!(second, Decibel(-100));
+}
+
+#[test]
+fn release_falls_at_the_configured_rate() {
+    let mut meter = MeterBallistics::new();
+    meter.set_target(&CONFIG, Decibel(-100), Decibel(-100), 0);
+    meter.set_target(&CONFIG, Decibel(-600), Decibel(-600), 0);
+    assert_eq!(meter.advance(&CONFIG, 500).level, Decibel(-200));
+    assert_eq!(meter.advance(&CONFIG, 10_000).level, Decibel(-600));
+}
+
+#[test]
+fn peak_holds_then_falls() {
+    let mut meter = MeterBallistics::new();
+    meter.set_target(&CONFIG, Decibel(-600), Decibel(-100), 0);
+    assert_eq!(meter.advance(&CONFIG, 900).peak, Decibel(-100));
+    assert_eq!(meter.advance(&CONFIG, 1500).peak, Decibel(-150));
+    assert_eq!(meter.advance(&CONFIG, 60_000).peak, Decibel(-600));
+}
+
+#[test]
+fn time_going_backwards_is_ignored() {
+    let mut meter = MeterBallistics::new();
+    meter.set_target(&CONFIG, Decibel(-100), Decibel(-100), 1000);
+    meter.set_target(&CONFIG, Decibel(-600), Decibel(-600), 1000);
+    let reading = meter.advance(&CONFIG, 0);
+    assert_eq!(reading.level, Decibel(-100));
+}
+
+#[test]
+fn meters_stay_with_their_track_when_tracks_move() {
+    let status = |tracks: &[(u16, Decibel)]| ReaperStatus::<4> {
+        tracks: tracks
+            .iter()
+            .map(|(track_number, level)| TrackData {
+                track_number: *track_number,
+                last_meter_pos: *level,
+                last_meter_peak: *level,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let peak = |bank: &MeterBank<4>, track_number| bank.reading(track_number).map(|reading| reading.peak);
+    let mut bank = MeterBank::<4>::new(CONFIG);
+    bank.update(&status(&[(1, Decibel(-100)), (2, Decibel(-600))]), 0);
+
+    // a new track in front of the others doesn't take over the held peak of track 1
+    bank.update(&status(&[(3, Decibel(-600)), (1, Decibel(-600)), (2, Decibel(-600))]), 100);
+    assert_eq!(peak(&bank, 3), Some(Decibel(-600)));
+    assert_eq!(peak(&bank, 1), Some(Decibel(-100)));
+    assert_eq!(peak(&bank, 2), Some(Decibel(-600)));
+
+    bank.update(&status(&[(3, Decibel(-600)), (2, Decibel(-600))]), 200);
+    assert_eq!(peak(&bank, 1), None);
+    assert_eq!(peak(&bank, 2), Some(Decibel(-600)));
+}
@@ -0,0 +1,57 @@
+use reaper::{Decibel, PlayState, ReaperStatus, TrackData};
+use renderer::{ClipLatches, ClipResetPolicy};
+
+fn status(play_state: PlayState, levels: &[Decibel]) -> ReaperStatus<4> {
+    let mut status = ReaperStatus::<4> {
+        tracks: levels
+            .iter()
+            .enumerate()
+            .map(|(index, level)| TrackData {
+                track_number: index as u16 + 1,
+                last_meter_pos: *level,
+                last_meter_peak: *level,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    status.transport.play_state = play_state;
+    status
+}
+
+const QUIET: Decibel = Decibel(-200);
+const CLIPPING: Decibel = Decibel(5);
+
+#[test]
+fn clips_latch_after_the_level_drops() {
+    let mut clips = ClipLatches::<4>::new(ClipResetPolicy::default());
+    clips.update(&status(PlayState::Playing, &[QUIET, CLIPPING]), 0);
+    clips.update(&status(PlayState::Playing, &[QUIET, QUIET]), 100);
+    assert!(!clips.is_latched(1));
+    assert!(clips.is_latched(2));
+    clips.reset_track(2);
+    assert!(!clips.is_latched(2));
+}
+
+#[test]
+fn transport_start_resets_clips() {
+    let mut clips = ClipLatches::<4>::new(ClipResetPolicy::default());
+    clips.update(&status(PlayState::Playing, &[CLIPPING]), 0);
+    clips.update(&status(PlayState::Stopped, &[QUIET]), 100);
+    assert!(clips.is_latched(1));
+    clips.update(&status(PlayState::Recording, &[QUIET]), 200);
+    assert!(!clips.is_latched(1));
+}
+
+#[test]
+fn clips_time_out() {
+    let mut clips = ClipLatches::<4>::new(ClipResetPolicy {
+        on_transport_start: false,
+        timeout_millis: Some(1000),
+    });
+    clips.update(&status(PlayState::Playing, &[CLIPPING]), 0);
+    clips.advance(999);
+    assert!(clips.is_latched(1));
+    clips.advance(1000);
+    assert!(!clips.is_latched(1));
+}
@@ -0,0 +1,205 @@
+//! Renders canonical statuses and compares them against the frames in `tests/snapshots`.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to (re)write the reference frames after an intended change to the layout.
+
+use reaper::{ColorByteOrder, Decibel, FolderDepths, PlayState, RawTrackFlags, ReaperStatus, Replay, TrackColor, TrackData, TrackFlags};
+use renderer::{BallisticsConfig, ClipLatches, ClipResetPolicy, Framebuffer, MeterBank, ReaperStatusRenderExt, RenderOptions};
+use std::{fs, path::PathBuf};
+
+const MAX_TRACK_COUNT: usize = 128;
+type Status = ReaperStatus<MAX_TRACK_COUNT>;
+type Panel = Framebuffer<64, 64>;
+
+/// spreads the levels so neighbouring meters differ
+fn level(index: usize) -> Decibel {
+    Decibel(-600 + ((index * 97) % 560) as i16)
+}
+
+/// `track_count` tracks including the master track, each at its own level
+fn status(play_state: PlayState, track_count: usize) -> Status {
+    let mut status = Status {
+        tracks: (0..track_count)
+            .map(|index| TrackData {
+                track_number: index as u16,
+                last_meter_pos: level(index),
+                last_meter_peak: level(index),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+    status.transport.play_state = play_state;
+    status
+}
+
+/// shows which rows of pixels differ, with a `^` under every pixel that changed
+fn diff(expected: &str, actual: &str) -> String {
+    let (expected, actual) = (expected.lines().collect::<Vec<_>>(), actual.lines().collect::<Vec<_>>());
+    (0..expected.len().max(actual.len()))
+        .map(|row| (row, expected.get(row).copied().unwrap_or_default(), actual.get(row).copied().unwrap_or_default()))
+        .filter(|(_, expected, actual)| expected != actual)
+        .map(|(row, expected, actual)| {
+            let markers = expected
+                .chars()
+                .map(Some)
+                .chain(std::iter::repeat(None))
+                .zip(actual.chars().map(Some).chain(std::iter::repeat(None)))
+                .take(expected.len().max(actual.len()))
+                .map(|(expected, actual)| if expected == actual { ' ' } else { '^' })
+                .collect::<String>();
+            format!("y={row:<2} - {expected}\n     + {actual}\n       {markers}\n")
+        })
+        .collect()
+}
+
+fn assert_snapshot(name: &str, status: &Status) {
+    let mut panel = Panel::default();
+    status.render(&mut panel).expect("rendering");
+    assert_frame(name, &panel);
+}
+
+fn assert_frame(name: &str, panel: &Panel) {
+    let actual = panel.to_ascii();
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(format!("{name}.txt"));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, &actual).expect("writing snapshot");
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| panic!("no snapshot at {}, run with UPDATE_SNAPSHOTS=1 to create it", path.display()));
+    if actual != expected {
+        let frame = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.ppm"));
+        fs::write(&frame, panel.to_ppm()).expect("writing the actual frame");
+        panic!("{name} doesn't match its snapshot, the actual frame is at {}\n{}", frame.display(), diff(&expected, &actual));
+    }
+}
+
+#[test]
+fn play_states() {
+    [
+        ("stopped", PlayState::Stopped),
+        ("playing", PlayState::Playing),
+        ("paused", PlayState::Paused),
+        ("recording", PlayState::Recording),
+        ("record_paused", PlayState::RecordPaused),
+    ]
+    .into_iter()
+    .for_each(|(name, play_state)| assert_snapshot(name, &status(play_state, 8)));
+}
+
+#[test]
+fn track_counts() {
+    [1, 8, 64]
+        .into_iter()
+        .for_each(|track_count| assert_snapshot(&format!("tracks_{track_count}"), &status(PlayState::Playing, track_count)));
+}
+
+#[test]
+fn muted_tracks() {
+    let mut status = status(PlayState::Playing, 8);
+    [2, 5]
+        .into_iter()
+        .for_each(|index| status.tracks[index].flags = RawTrackFlags(TrackFlags::Muted as u16));
+    assert_snapshot("muted", &status);
+}
+
+#[test]
+fn clipping_tracks() {
+    let mut status = status(PlayState::Playing, 8);
+    status.tracks[3].last_meter_peak = Decibel(5);
+    // muted wins over clipping
+    status.tracks[5].last_meter_pos = Decibel(12);
+    status.tracks[5].flags = RawTrackFlags(TrackFlags::Muted as u16);
+    assert_snapshot("clipping", &status);
+}
+
+#[test]
+fn track_colors() {
+    let mut status = status(PlayState::Playing, 8);
+    status.tracks[1].color = TrackColor::from_rgb((255, 0, 128), ColorByteOrder::default());
+    status.tracks[2].color = TrackColor::from_rgb((40, 80, 200), ColorByteOrder::default());
+    assert_snapshot("track_colors", &status);
+}
+
+#[test]
+fn folders_group_meters_by_bus() {
+    let mut status = status(PlayState::Playing, 8);
+    // master, Drums { 2, 3 }, 4, Bass { 6 }, 7
+    [0, 1, 0, -1, 0, 1, -1, 0]
+        .into_iter()
+        .zip(status.tracks.iter_mut())
+        .for_each(|(depth, track)| track.folder_depth = Some(depth));
+    status.tracks[5].color = TrackColor::from_rgb((40, 80, 200), ColorByteOrder::default());
+    assert_snapshot("folders_grouped", &status);
+}
+
+#[test]
+fn folders_collapse_when_tracks_overflow() {
+    let mut status = status(PlayState::Playing, 80);
+    status
+        .tracks
+        .iter_mut()
+        .for_each(|track| track.folder_depth = Some(0));
+    status.tracks[1].folder_depth = Some(1);
+    status.tracks[20].folder_depth = Some(-1);
+    assert_snapshot("folders_collapsed", &status);
+}
+
+/// replays the session recorded from the mock server with the firmware's frame rate, meters and clip latches
+#[test]
+fn recorded_session() {
+    const FRAME_MILLIS: u64 = 20;
+    let mut replay = Replay::new(include_bytes!("../../reaper/tests/recordings/session.rec"), 1.);
+    let mut folder_depths = FolderDepths::<MAX_TRACK_COUNT>::new();
+    let mut meters = MeterBank::<MAX_TRACK_COUNT>::new(BallisticsConfig::default());
+    let mut clips = ClipLatches::<MAX_TRACK_COUNT>::new(ClipResetPolicy::default());
+    let mut status = Status::default();
+    let checkpoints = [(600, "session_600ms"), (1800, "session_1800ms"), (3500, "session_end")];
+
+    (0..=3500).step_by(FRAME_MILLIS as usize).for_each(|now| {
+        while let Some(response) = replay.next_due(now) {
+            let Some(updated) = response
+                .and_then(|response| folder_depths.replay(&response))
+                .expect("every recorded response parses")
+            else {
+                continue;
+            };
+            meters.update(&updated, now);
+            clips.update(&updated, now);
+            status = updated;
+        }
+        meters.advance(now);
+        clips.advance(now);
+        if let Some((_, name)) = checkpoints.iter().find(|(at, _)| *at == now) {
+            let mut panel = Panel::default();
+            status
+                .render_with_options(
+                    &mut panel,
+                    &RenderOptions {
+                        meters: Some(&meters),
+                        clips: Some(&clips),
+                        ..Default::default()
+                    },
+                )
+                .expect("rendering");
+            assert_frame(name, &panel);
+        }
+    });
+    assert!(replay.is_finished());
+}
+
+#[test]
+fn ascii_lists_unnamed_colors() {
+    use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+    let mut panel = Framebuffer::<3, 2>::default();
+    panel
+        .draw_iter([
+            Pixel(Point::new(0, 0), Rgb888::GREEN),
+            Pixel(Point::new(1, 0), Rgb888::new(1, 2, 3)),
+            Pixel(Point::new(2, 1), Rgb888::new(1, 2, 3)),
+            Pixel(Point::new(5, 5), Rgb888::RED),
+        ])
+        .expect("drawing");
+    assert_eq!(panel.to_ascii(), "Ga.\n..a\na #010203\n");
+    assert_eq!(panel.to_ppm()[..11], *b"P6\n3 2\n255\n");
+    assert_eq!(panel.to_ppm().len(), 11 + 3 * 2 * 3);
+}
@@ -16,11 +16,12 @@ use embassy_rp::{
 };
 use embassy_sync::channel::{Channel, Sender};
 use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Receiver};
-use embassy_time::{with_timeout, Delay, Duration, Ticker, Timer};
+use embassy_time::{with_timeout, Delay, Duration, Instant, Ticker, Timer};
 use embedded_wrap_err::{IntoWrapErrExt, Result, WrapErrorExt as _};
 use futures::FutureExt;
 use log::error;
 use reaper::ReaperStatus;
+use renderer::{BallisticsConfig, ClipLatches, ClipResetPolicy, MeterBank};
 use static_cell::StaticCell;
 use status_bar_display::MyMatrixDisplay;
 use tap::prelude::*;
@@ -33,7 +34,7 @@ use embassy_executor::Spawner;
 use embassy_rp::bind_interrupts;
 use {defmt_rtt as _, panic_probe as _};
 
-pub mod reaper_diagnostic_fetch;
+pub mod osc_listener;
 pub mod status_bar_display;
 
 bind_interrupts!(struct Irqs {
@@ -43,16 +44,51 @@ bind_interrupts!(struct Irqs {
 const ESP_WIFI_SSID: &str = env!("ESP_WIFI_SSID");
 const ESP_WIFI_PASSWORD: &str = env!("ESP_WIFI_PASSWORD");
 const ESP_REAPER_BASE_URL: &str = env!("ESP_REAPER_BASE_URL");
+/// where Reaper's OSC control surface sends to, only used with the `osc-push` feature
+const ESP_REAPER_OSC_PORT: Option<&str> = option_env!("ESP_REAPER_OSC_PORT");
+const DEFAULT_OSC_PORT: u16 = 9000;
+const OSC_PORT: u16 = match ESP_REAPER_OSC_PORT {
+    Some(port) => parse_port(port),
+    None => DEFAULT_OSC_PORT,
+};
+
+/// evaluated at compile time, so a bad `ESP_REAPER_OSC_PORT` fails the build instead of the boot
+const fn parse_port(port: &str) -> u16 {
+    let digits = port.as_bytes();
+    assert!(!digits.is_empty(), "ESP_REAPER_OSC_PORT is empty");
+    let mut value = 0u32;
+    let mut index = 0;
+    while index < digits.len() {
+        assert!(digits[index].is_ascii_digit(), "ESP_REAPER_OSC_PORT is not a port number");
+        value = value * 10 + (digits[index] - b'0') as u32;
+        assert!(value <= u16::MAX as u32, "ESP_REAPER_OSC_PORT is not a port number");
+        index += 1;
+    }
+    value as u16
+}
 
 const MAX_HEADER_SIZE: usize = 512;
+/// one track per column of the panel. Folders only collapse with more tracks than columns, which would
+/// double every status held in the channel, the client and the display task
 const MAX_TRACK_COUNT: usize = 64;
-const MAX_TRACK_LINE_SIZE: usize = 128;
+/// the response body is parsed line by line, so only a single line has to fit in memory
+const MAX_LINE_SIZE: usize = 256;
+const BODY_CHUNK_SIZE: usize = 256;
+/// enough for a `TRACK` range followed by a folder depth command for each of its tracks
+const MAX_PATH_SIZE: usize = 512;
 
-const MAX_RESPONSE_SIZE: usize = (MAX_TRACK_COUNT + 1) * MAX_TRACK_LINE_SIZE;
+const RX_BUFFER_SIZE: usize = MAX_HEADER_SIZE + BODY_CHUNK_SIZE;
 
-const IO_BUFFER_SIZE: usize = MAX_HEADER_SIZE + MAX_RESPONSE_SIZE;
+type ReaperClient<'stack, T> = reaper_client::ReaperClient<'stack, T, MAX_TRACK_COUNT, MAX_LINE_SIZE, RX_BUFFER_SIZE, BODY_CHUNK_SIZE, MAX_PATH_SIZE>;
 
-const MAX_MESSAGE_COUNT: usize = 8;
+const IO_BUFFER_SIZE: usize = 2048;
+
+/// a status is about 3.7 KB with 64 tracks and the display only ever shows the latest one,
+/// so the poller may get at most one status ahead of it
+const MAX_MESSAGE_COUNT: usize = 2;
+
+/// folders change rarely and take a request per chunk of tracks, so they are only re-fetched every few polls
+const FOLDER_DEPTH_REFRESH_POLLS: usize = 200;
 
 static REAPER_STATE_CHANNEL: Channel<CriticalSectionRawMutex, ReaperStatus<MAX_TRACK_COUNT>, MAX_MESSAGE_COUNT> = Channel::new();
 
@@ -136,39 +172,46 @@ fn main() -> ! {
 
 static MY_MATRIX_DISPLAY: StaticCell<MyMatrixDisplay> = StaticCell::new();
 
+/// how often the smoothed meters are re-rendered between status updates
+const METER_FRAME_MILLIS: u64 = 20;
+
 #[embassy_executor::task]
 async fn keep_redrawing_screen(updates: Receiver<'static, CriticalSectionRawMutex, ReaperStatus<MAX_TRACK_COUNT>, MAX_MESSAGE_COUNT>, display: &'static mut MyMatrixDisplay) {
     info!("screen task running");
     let mut delay = Delay;
+    let mut meters = MeterBank::<MAX_TRACK_COUNT>::new(BallisticsConfig::default());
+    let mut clips = ClipLatches::<MAX_TRACK_COUNT>::new(ClipResetPolicy::default());
+    let mut status = ReaperStatus::<MAX_TRACK_COUNT>::default();
+    let mut last_frame = Instant::now().as_millis();
     display
-        .update_display_data(&Default::default())
+        .update_display_data(&status)
         .expect("could not redraw even once");
     loop {
-        info!("checking if there's some data");
-        if let Ok(updated) = {
-            let updates = updates.try_receive();
-            info!("checked");
-            updates
-        } {
+        let now = Instant::now().as_millis();
+        let received = updates.try_receive().ok();
+        if let Some(updated) = received.as_ref() {
+            meters.update(updated, now);
+            clips.update(updated, now);
+        }
+        if received.is_some() || now.saturating_sub(last_frame) >= METER_FRAME_MILLIS {
+            last_frame = now;
+            meters.advance(now);
+            clips.advance(now);
+            if let Some(updated) = received {
+                status = updated;
+            }
             match display
-                .update_display_data(&updated)
-                .wrap_err("received data but couldn't render")
+                .update_display_data_with_meters(&status, &meters, &clips)
+                .wrap_err("couldn't render meters")
             {
-                Ok(_) => {
-                    info!("updated the screen");
-                }
+                Ok(_) => {}
                 Err(message) => {
                     debug!("reason: {}", message);
                 }
             }
         }
 
-        info!("starting to draw");
-        if let Err(message) = {
-            let draw = display.draw(&mut delay);
-            info!("draw finished");
-            draw
-        } {
+        if let Err(message) = display.draw(&mut delay) {
             error!("couldn't draw: {message}");
         }
     }
@@ -271,18 +314,31 @@ async fn actual_main(_spawner: Spawner, stack: NetworkStack, sender: Sender<'sta
     let mut client = reqwless::client::HttpClient::new(&tcp_client, &dns_socket);
 
     info!("created a http client");
-    let mut client = reaper_diagnostic_fetch::ReaperClient::new(&mut client, ESP_REAPER_BASE_URL)
+    let mut client = ReaperClient::new(&mut client, ESP_REAPER_BASE_URL)
         .await
         .wrap_err("building reaper client")?;
     info!("created an reaper client");
 
+    let mut poll = 0usize;
     loop {
+        if poll % FOLDER_DEPTH_REFRESH_POLLS == 0 {
+            // folders only change the layout, so the panel keeps going with the depths it already has.
+            // The client reads every response to its end, but a timeout leaves one half read on the connection.
+            if let Err(message) = with_timeout(Duration::from_millis(5000), client.refresh_folder_depths())
+                .await
+                .into_wrap_err("timeout occurred")
+                .wrap_err("refreshing folder depths")?
+            {
+                warn!("refreshing folder depths: {}", message);
+            }
+        }
         let status = with_timeout(Duration::from_millis(5000), client.get_status())
             .await
             .into_wrap_err("timeout occurred")
             .and_then(|out| out)
             .wrap_err("fetching reaper status")?;
         sender.send(status).await;
+        poll = poll.wrapping_add(1);
 
         Timer::after(Duration::from_millis(50)).await;
     }
@@ -297,7 +353,10 @@ macro_rules! debug_env {
 
 use embassy_executor::Executor;
 
-static mut CORE1_STACK: embassy_rp::multicore::Stack<{ 1024 * 4 }> = embassy_rp::multicore::Stack::new();
+/// the display task never awaits, so everything it keeps lives on this stack: the status it shows and
+/// the one it just received (3712 B each with 64 tracks), the meter bank (3096 B), the clip latches
+/// (1048 B) and the folder tree of every frame (772 B), about 12.1 KB before any drawing
+static mut CORE1_STACK: embassy_rp::multicore::Stack<{ 1024 * 16 }> = embassy_rp::multicore::Stack::new();
 static EXECUTOR0: StaticCell<Executor> = StaticCell::new();
 static EXECUTOR1: StaticCell<Executor> = StaticCell::new();
 
@@ -312,7 +371,11 @@ async fn embassy_main(spawner: Spawner, wifi_setup_context: SetupWifiContext) {
         .expect("failed to setup network stack");
     info!("wifi setup correctly, starting the main task");
     loop {
-        match actual_main(spawner, stack, REAPER_STATE_CHANNEL.sender()).await {
+        let result = match cfg!(feature = "osc-push") {
+            true => osc_listener::listen_osc(stack, OSC_PORT, REAPER_STATE_CHANNEL.sender()).await,
+            false => actual_main(spawner, stack, REAPER_STATE_CHANNEL.sender()).await,
+        };
+        match result {
             Ok(_) => info!("app just finished"),
             Err(message) => {
                 info!("ERROR: {}. (restarting)", message);
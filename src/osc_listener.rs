@@ -0,0 +1,62 @@
+use crate::{NetworkStack, MAX_MESSAGE_COUNT, MAX_TRACK_COUNT};
+use defmt::*;
+use embassy_net::udp::{PacketMetadata, UdpSocket};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Sender};
+use embassy_time::{with_timeout, Duration, Instant};
+use embedded_wrap_err::{IntoWrapErrDebugExt, Result};
+use reaper::{DatagramSource, OscListener, ReaperStatus};
+
+/// a full ethernet frame, Reaper splits its bundles well below that
+const MAX_OSC_DATAGRAM_SIZE: usize = 1536;
+const OSC_RX_BUFFER_SIZE: usize = MAX_OSC_DATAGRAM_SIZE * 4;
+const OSC_PACKET_METADATA_COUNT: usize = 16;
+/// Reaper sends a datagram per change, the display doesn't need more than this
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(20);
+
+struct EmbassyUdp<'a>(UdpSocket<'a>);
+
+impl DatagramSource for EmbassyUdp<'_> {
+    async fn receive(&mut self, buffer: &mut [u8]) -> Result<usize> {
+        self.0
+            .recv_from(buffer)
+            .await
+            .into_wrap_err_dbg("receiving udp datagram")
+            .map(|(length, _endpoint)| length)
+    }
+}
+
+/// listens for Reaper's OSC output on `port` and publishes the accumulated status at most every [`PUBLISH_INTERVAL`]
+pub async fn listen_osc(stack: NetworkStack, port: u16, sender: Sender<'static, CriticalSectionRawMutex, ReaperStatus<MAX_TRACK_COUNT>, MAX_MESSAGE_COUNT>) -> Result<()> {
+    let mut rx_meta = [PacketMetadata::EMPTY; OSC_PACKET_METADATA_COUNT];
+    let mut rx_buffer = [0; OSC_RX_BUFFER_SIZE];
+    // nothing is ever sent back
+    let mut tx_meta = [PacketMetadata::EMPTY; 1];
+    let mut tx_buffer = [0; 1];
+    let mut socket = UdpSocket::new(stack, &mut rx_meta, &mut rx_buffer, &mut tx_meta, &mut tx_buffer);
+    socket.bind(port).into_wrap_err_dbg("binding osc socket")?;
+    info!("listening for OSC on port {}", port);
+
+    let mut listener = OscListener::<_, MAX_TRACK_COUNT, MAX_OSC_DATAGRAM_SIZE>::new(EmbassyUdp(socket));
+    let mut last_publish = Instant::now();
+    let mut unpublished = false;
+    loop {
+        // wake up in time to publish changes even when Reaper goes quiet
+        let timeout = match unpublished {
+            true => PUBLISH_INTERVAL.checked_sub(last_publish.elapsed()).unwrap_or(Duration::from_ticks(0)),
+            false => Duration::from_secs(60),
+        };
+        match with_timeout(timeout, listener.receive()).await {
+            Ok(Ok(_status)) => unpublished = true,
+            Ok(Err(message)) => warn!("skipping OSC datagram: {}", message),
+            Err(_timeout) => {}
+        }
+        if unpublished && last_publish.elapsed() >= PUBLISH_INTERVAL {
+            // a full channel means the display is behind, so the next attempt waits another interval.
+            // Retrying right away would time out at once and keep this core from ever yielding to the network.
+            unpublished = sender
+                .try_send(listener.accumulator.status().clone())
+                .is_err();
+            last_publish = Instant::now();
+        }
+    }
+}
@@ -2,7 +2,7 @@ use super::*;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_hal::blocking::delay::DelayUs;
 use embedded_wrap_err::WrapErrorExt;
-use renderer::ReaperStatusRenderExt;
+use renderer::{ClipLatches, MeterBank, ReaperStatusRenderExt, RenderOptions};
 
 // type WiringPin<const INDEX: u8> = GpioPin<Output<PushPull>, INDEX>;
 
@@ -118,6 +118,7 @@ impl MyMatrixDisplay {
         self.0.output(delay).into_wrap_err("displaying output")
     }
     pub fn update_display_data(&mut self, status: &ReaperStatus<MAX_TRACK_COUNT>) -> Result<()> {
+        trace!("rendering status: {}", status);
         self.0.clear();
         status
             .render(&mut self.0)
@@ -125,4 +126,19 @@ impl MyMatrixDisplay {
         debug!("new state: {:?}", &self.0.data.last());
         Ok(())
     }
+    /// like [`MyMatrixDisplay::update_display_data`], but draws the smoothed meter levels instead of the raw ones
+    /// and lights the clip indicators of every latched track
+    pub fn update_display_data_with_meters(&mut self, status: &ReaperStatus<MAX_TRACK_COUNT>, meters: &MeterBank<MAX_TRACK_COUNT>, clips: &ClipLatches<MAX_TRACK_COUNT>) -> Result<()> {
+        self.0.clear();
+        status
+            .render_with_options(
+                &mut self.0,
+                &RenderOptions {
+                    meters: Some(meters),
+                    clips: Some(clips),
+                    ..Default::default()
+                },
+            )
+            .wrap_err("updating render data")
+    }
 }